@@ -0,0 +1,237 @@
+//! TOML configuration file read by `--config`, so that Nagios command
+//! definitions stay short and the policy is kept in one place.
+//!
+//! Keys are the long names of the options, with underscores or dashes, and
+//! `domains` lists domain names. Options given on the command line or
+//! through the environment take over those of the file, and so do they over
+//! the options they conflict with: `--min-grade B` drops the `warning`,
+//! `critical` and `grade_map` below, `--ipv6-only` an `ipv4_only` and
+//! `--start-new` a `from_cache`.
+//!
+//! ```toml
+//! api_url = "https://api.ssllabs.com/api/v3/"
+//! proxy = "http://proxy.example.com:3128"
+//! warning = "A+"
+//! critical = "A"
+//! grade_map = "A+=0,A=0,A-=1,B=1,*=2"
+//! ignore = ["trust"]
+//! backoff = 2.0
+//! max_delay = 120
+//! deny_wildcard = true
+//! require_hsts = 15768000
+//! ```
+
+use crate::Cli;
+use clap::error::ErrorKind;
+use clap::parser::ValueSource;
+use clap::{Arg, ArgAction, ArgMatches, Command, CommandFactory, FromArgMatches};
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use toml::Value;
+
+/// Parse the command line along the configuration file it names, if any.
+pub fn parse() -> Result<Cli, clap::Error> {
+    let args: Vec<OsString> = env::args_os().collect();
+    let mut command = Cli::command();
+    command.build();
+    // Domain names and the like may only come from the file, the first
+    // pass only looks for it and what is already given
+    let matches = command
+        .clone()
+        .ignore_errors(true)
+        .try_get_matches_from(&args)?;
+    let Some(path) = matches.get_one::<String>("config") else {
+        return Cli::from_arg_matches(&command.try_get_matches_from(args)?);
+    };
+    let options = read(path, &command, &matches)
+        .map_err(|e| command.error(ErrorKind::InvalidValue, format!("{}: {}", path, e)))?;
+    let mut merged = args[..1].to_vec();
+    merged.extend(options);
+    merged.extend_from_slice(&args[1..]);
+    Cli::from_arg_matches(&command.try_get_matches_from(merged)?)
+}
+
+/// The options of the file as command line arguments, leaving out those
+/// already given or conflicting with one given.
+fn read(path: &str, command: &Command, matches: &ArgMatches) -> Result<Vec<OsString>, String> {
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let table: toml::value::Table = toml::from_str(&content).map_err(|e| e.to_string())?;
+    let given: Vec<&Arg> = command
+        .get_arguments()
+        .filter(|arg| {
+            matches!(
+                matches.value_source(arg.get_id().as_str()),
+                Some(ValueSource::CommandLine | ValueSource::EnvVariable)
+            )
+        })
+        .collect();
+    let mut options = Vec::new();
+    let mut domains = Vec::new();
+    for (key, value) in &table {
+        let id = key.replace('-', "_");
+        let arg = command
+            .get_arguments()
+            .filter(|_| !matches!(id.as_str(), "config" | "help" | "version"))
+            .find(|arg| arg.get_id() == id.as_str())
+            .ok_or_else(|| format!("unknown option {}", key))?;
+        if given
+            .iter()
+            .any(|other| other.get_id() == arg.get_id() || conflict(command, arg, other))
+        {
+            continue;
+        }
+        let values = match value {
+            Value::Array(values) => values.iter().collect(),
+            value => vec![value],
+        };
+        let Some(long) = arg.get_long() else {
+            for value in values {
+                domains.push(OsString::from(scalar(key, value)?));
+            }
+            continue;
+        };
+        // Flags only take true, options with an optional value take it bare
+        let flag = !arg.get_action().takes_values()
+            || arg
+                .get_num_args()
+                .is_some_and(|range| range.min_values() == 0);
+        for value in values {
+            match value {
+                // Counted flags such as verbosity take their count
+                Value::Integer(count) if matches!(arg.get_action(), ArgAction::Count) => {
+                    for _ in 0..*count {
+                        options.push(OsString::from(format!("--{}", long)));
+                    }
+                }
+                Value::Boolean(true) if flag => options.push(OsString::from(format!("--{}", long))),
+                Value::Boolean(false) if flag => (),
+                value => options.push(OsString::from(format!(
+                    "--{}={}",
+                    long,
+                    scalar(key, value)?
+                ))),
+            }
+        }
+    }
+    options.extend(domains);
+    Ok(options)
+}
+
+/// Whether the two options can't be given together, whichever of them
+/// declares the conflict.
+fn conflict(command: &Command, arg: &Arg, other: &Arg) -> bool {
+    let declares = |arg: &Arg, other: &Arg| {
+        command
+            .get_arg_conflicts_with(arg)
+            .iter()
+            .any(|conflict| conflict.get_id() == other.get_id())
+    };
+    declares(arg, other) || declares(other, arg)
+}
+
+fn scalar(key: &str, value: &Value) -> Result<String, String> {
+    match value {
+        Value::String(value) => Ok(value.clone()),
+        Value::Integer(value) => Ok(value.to_string()),
+        Value::Float(value) => Ok(value.to_string()),
+        Value::Boolean(value) => Ok(value.to_string()),
+        Value::Datetime(value) => Ok(value.to_string()),
+        Value::Array(_) | Value::Table(_) => {
+            Err(format!("{}: a value or a list of values expected", key))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// The options a file gives along the command line arguments.
+    fn options(config: &str, args: &[&str]) -> Result<Vec<String>, String> {
+        static FILES: AtomicUsize = AtomicUsize::new(0);
+        let file = FILES.fetch_add(1, Ordering::Relaxed);
+        let path = env::temp_dir().join(format!("check_qualys-{}-{}.toml", process::id(), file));
+        fs::write(&path, config).unwrap();
+        let mut command = Cli::command();
+        command.build();
+        let args = std::iter::once(&"check_qualys").chain(args);
+        let matches = command
+            .clone()
+            .ignore_errors(true)
+            .try_get_matches_from(args)
+            .unwrap();
+        let options = read(path.to_str().unwrap(), &command, &matches);
+        fs::remove_file(&path).unwrap();
+        Ok(options?
+            .into_iter()
+            .map(|option| option.into_string().unwrap())
+            .collect())
+    }
+
+    #[test]
+    fn merge() {
+        let cases: &[(&str, &[&str], &[&str])] = &[
+            ("warning = \"A+\"", &[], &["--warning=A+"]),
+            ("max-delay = 120", &[], &["--max-delay=120"]),
+            ("deny_wildcard = true", &[], &["--deny-wildcard"]),
+            ("deny_wildcard = false", &[], &[]),
+            ("verbose = 2", &[], &["--verbose", "--verbose"]),
+            (
+                "ignore = [\"trust\", \"mismatch\"]",
+                &[],
+                &["--ignore=trust", "--ignore=mismatch"],
+            ),
+            (
+                "domains = [\"a.example.com\"]\ncritical = \"B\"",
+                &[],
+                &["--critical=B", "a.example.com"],
+            ),
+            ("warning = \"A+\"", &["--warning", "B"], &[]),
+            (
+                "warning = \"A+\"\ncritical = \"B\"",
+                &["-w", "A"],
+                &["--critical=B"],
+            ),
+        ];
+        for (config, args, expected) in cases {
+            assert_eq!(options(config, args).unwrap(), *expected, "{}", config);
+        }
+    }
+
+    #[test]
+    fn conflicts() {
+        let cases: &[(&str, &[&str], &[&str])] = &[
+            ("ipv4_only = true", &["--ipv6-only"], &[]),
+            ("from_cache = true", &["--start-new"], &[]),
+            (
+                "warning = \"A\"\ncritical = \"B\"",
+                &["--min-grade", "B"],
+                &[],
+            ),
+            ("grade_map = \"*=0\"", &["--min-grade", "B"], &[]),
+            ("min_grade = \"A\"", &["--warning", "B"], &[]),
+            (
+                "min_grade = \"A\"",
+                &["--deny-wildcard"],
+                &["--min-grade=A"],
+            ),
+        ];
+        for (config, args, expected) in cases {
+            assert_eq!(options(config, args).unwrap(), *expected, "{}", config);
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        for config in [
+            "nope = 1",
+            "config = \"other.toml\"",
+            "warning = { grade = \"A\" }",
+        ] {
+            assert!(options(config, &[]).is_err(), "{}", config);
+        }
+    }
+}
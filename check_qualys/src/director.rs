@@ -0,0 +1,211 @@
+use crate::i18n::tr;
+use crate::inventory::Inventory;
+use crate::policy::{AddressFamily, Policy};
+use clap::{Args, ValueEnum};
+use reqwest::blocking::Client;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use std::error::Error;
+
+#[derive(Args, Debug)]
+pub struct RegisterArgs {
+    /// YAML inventory of the domains to register
+    pub inventory: String,
+
+    /// Base URL of Icinga Director, e.g. https://icinga.example.com/icingaweb2/director
+    #[arg(long, env = "ICINGA_DIRECTOR_URL", hide_env_values = true)]
+    pub director_url: String,
+
+    /// User of the Director API
+    #[arg(long, env = "ICINGA_DIRECTOR_USER")]
+    pub director_user: String,
+
+    /// Password of the Director API user
+    #[arg(long, env = "ICINGA_DIRECTOR_PASSWORD", hide_env_values = true)]
+    pub director_password: String,
+
+    /// Template imported by the host objects
+    #[arg(long, default_value = "generic-host")]
+    pub host_template: String,
+
+    /// Template imported by the service objects
+    #[arg(long, default_value = "generic-service")]
+    pub service_template: String,
+
+    /// Name of the service object created on each host
+    #[arg(long, default_value = "ssl-labs")]
+    pub service_name: String,
+
+    /// Check command running this plugin
+    #[arg(long, default_value = "check_qualys")]
+    pub check_command: String,
+
+    /// Deploy the Director configuration once the objects are registered
+    #[arg(long)]
+    pub deploy: bool,
+}
+
+struct Director<'a> {
+    client: Client,
+    args: &'a RegisterArgs,
+}
+
+impl Director<'_> {
+    fn url(&self, path: &str) -> String {
+        format!("{}/{}", self.args.director_url.trim_end_matches('/'), path)
+    }
+
+    /// Create the object, or update it when the Director already knows it.
+    fn upsert(
+        &self,
+        kind: &str,
+        query: &[(&str, &str)],
+        object: Value,
+    ) -> Result<(), Box<dyn Error>> {
+        let url = self.url(kind);
+        let existing = self
+            .client
+            .get(&url)
+            .query(query)
+            .basic_auth(&self.args.director_user, Some(&self.args.director_password))
+            .header("Accept", "application/json")
+            .send()?;
+        // A refused login or a failing Director mustn't pass for an object
+        // to update
+        let request = match existing.status() {
+            StatusCode::NOT_FOUND => self.client.post(&url),
+            _ => {
+                existing.error_for_status()?;
+                self.client.post(&url).query(query)
+            }
+        };
+        request
+            .basic_auth(&self.args.director_user, Some(&self.args.director_password))
+            .header("Accept", "application/json")
+            .json(&object)
+            .send()?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    fn deploy(&self) -> Result<(), Box<dyn Error>> {
+        self.client
+            .post(self.url("config/deploy"))
+            .basic_auth(&self.args.director_user, Some(&self.args.director_password))
+            .header("Accept", "application/json")
+            .send()?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+/// Custom variables of the service, one for each option of the policy and
+/// valued as the option takes it, null when left to its default.
+fn vars(domain: &str, policy: &Policy, tags: &[String]) -> Value {
+    let protocols: Vec<String> = policy.forbid_protocols.iter().map(value_name).collect();
+    json!({
+        "qualys_domain": domain,
+        "qualys_warning": policy.warning.to_string(),
+        "qualys_critical": policy.critical.to_string(),
+        "qualys_grade_map": policy.grade_map.as_ref().map(|map| map.to_string()),
+        "qualys_ignore": policy.ignore,
+        "qualys_endpoint_policy": value_name(&policy.endpoint_policy),
+        "qualys_ipv4_only": policy.address_family == Some(AddressFamily::Ipv4),
+        "qualys_ipv6_only": policy.address_family == Some(AddressFamily::Ipv6),
+        "qualys_expect_endpoints": policy.expect_endpoints,
+        "qualys_require_consistent_grades": policy.require_consistent_grades,
+        "qualys_max_report_age": policy.max_report_age,
+        "qualys_warn_stale": policy.warn_stale,
+        "qualys_expiry_warning": policy.expiry_warning,
+        "qualys_expiry_critical": policy.expiry_critical,
+        "qualys_expect_issuer": policy.expect_issuer,
+        "qualys_deny_wildcard": policy.deny_wildcard,
+        "qualys_require_sct": policy.require_sct,
+        "qualys_require_caa": policy.require_caa,
+        "qualys_require_resumption": policy.require_resumption,
+        "qualys_deny_session_tickets": policy.deny_session_tickets,
+        "qualys_check_renegotiation": policy.check_renegotiation,
+        "qualys_deny_compression": policy.deny_compression,
+        "qualys_require_fallback_scsv": policy.require_fallback_scsv,
+        "qualys_deny_0rtt": policy.deny_0rtt,
+        "qualys_forbid_protocols": protocols,
+        "qualys_curve_policy": value_name(&policy.curve_policy),
+        "qualys_require_server_preference": policy.require_server_preference,
+        "qualys_deny_static_rsa": policy.deny_static_rsa,
+        "qualys_require_forward_secrecy": policy.require_forward_secrecy,
+        "qualys_min_key_strength": policy.min_key_strength,
+        "qualys_forbid_ciphers": policy.forbid_ciphers,
+        "qualys_require_hsts": policy.require_hsts,
+        "qualys_require_ocsp_stapling": policy.require_ocsp_stapling,
+        "qualys_check_chain": policy.check_chain,
+        "qualys_trust_store": policy.trust_store.as_ref().map(value_name),
+        "qualys_if_trust_issues": policy.if_trust_issues.as_ref().map(value_name),
+        "qualys_strict": policy.strict,
+        "qualys_check_openssl_ccs": policy.check_openssl_ccs,
+        "qualys_fail_on_vuln": policy.fail_on_vuln,
+        "qualys_tags": tags,
+    })
+}
+
+/// Name of an option value on the command line.
+fn value_name<T: ValueEnum>(value: &T) -> String {
+    value
+        .to_possible_value()
+        .map(|value| value.get_name().to_string())
+        .unwrap_or_default()
+}
+
+/// Register a host and its SSL Labs service for every inventory entry.
+pub fn register(
+    args: &RegisterArgs,
+    defaults: &Policy,
+    client: Client,
+    verbose: bool,
+) -> Result<(), Box<dyn Error>> {
+    let director = Director { client, args };
+    let inventory = Inventory::load(&args.inventory)?;
+    // The check command has no option for them, the service would lose them
+    if let Some(host) = inventory
+        .hosts
+        .iter()
+        .find(|host| !host.endpoints.is_empty())
+    {
+        return Err(tr("director_endpoint_overrides", &[&host.domain]).into());
+    }
+    for host in &inventory.hosts {
+        let policy = host.policy(defaults);
+        director.upsert(
+            "host",
+            &[("name", &host.domain)],
+            json!({
+                "object_name": host.domain,
+                "object_type": "object",
+                "imports": [args.host_template],
+                "address": host.domain,
+            }),
+        )?;
+        director.upsert(
+            "service",
+            &[("name", &args.service_name), ("host", &host.domain)],
+            json!({
+                "object_name": args.service_name,
+                "object_type": "object",
+                "host": host.domain,
+                "imports": [args.service_template],
+                "check_command": args.check_command,
+                "vars": vars(&host.domain, &policy, &host.tags),
+            }),
+        )?;
+        if verbose {
+            eprintln!("{}", tr("director_registered", &[&host.domain]));
+        }
+    }
+    if args.deploy {
+        director.deploy()?;
+    }
+    println!(
+        "{}",
+        tr("director_registered_hosts", &[&inventory.hosts.len()])
+    );
+    Ok(())
+}
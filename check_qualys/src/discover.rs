@@ -0,0 +1,120 @@
+use crate::i18n::tr;
+use crate::{blocking_builder, Cli};
+use clap::Args;
+use reqwest::blocking::Client;
+use serde::Deserialize;
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::fs;
+use std::thread;
+use std::time::Duration;
+
+/// Number of subdomains probed for HTTPS at the same time
+const PROBE_WORKERS: usize = 16;
+
+#[derive(Args, Debug)]
+pub struct DiscoverArgs {
+    /// Zone whose subdomains should be analysed
+    pub zone: String,
+
+    /// File of subdomain labels to try, one per line
+    #[arg(long)]
+    pub wordlist: Option<String>,
+
+    /// Skip the Certificate Transparency logs lookup on crt.sh
+    #[arg(long)]
+    pub no_ct_logs: bool,
+
+    /// Seconds to wait for a subdomain to answer over HTTPS
+    #[arg(long, default_value_t = 5)]
+    pub probe_timeout: u64,
+
+    /// Only list the subdomains serving HTTPS instead of analysing them
+    #[arg(long)]
+    pub list: bool,
+}
+
+#[derive(Deserialize, Debug)]
+struct CtLogEntry {
+    name_value: String,
+}
+
+/// Enumerate the subdomains of the zone and keep those serving HTTPS.
+pub fn discover(args: &DiscoverArgs, cli: &Cli) -> Result<Vec<String>, Box<dyn Error>> {
+    let verbose = cli.verbose >= 2;
+    let zone = args.zone.trim_end_matches('.').to_lowercase();
+    let mut candidates = BTreeSet::new();
+    if !args.no_ct_logs {
+        candidates.extend(from_ct_logs(&blocking_builder(cli)?.build()?, &zone)?);
+    }
+    if let Some(path) = &args.wordlist {
+        candidates.extend(from_wordlist(&zone, path)?);
+    }
+    if verbose {
+        eprintln!("{}", tr("discovered", &[&candidates.len()]));
+    }
+
+    let client = blocking_builder(cli)?
+        .timeout(Duration::from_secs(args.probe_timeout))
+        .danger_accept_invalid_certs(true)
+        .redirect(reqwest::redirect::Policy::none())
+        .build()?;
+    let candidates: Vec<String> = candidates.into_iter().collect();
+    let chunk_size = candidates.len().div_ceil(PROBE_WORKERS).max(1);
+    let serving = thread::scope(|scope| {
+        let workers: Vec<_> = candidates
+            .chunks(chunk_size)
+            .map(|chunk| {
+                let client = &client;
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .filter(|name| serves_https(client, name))
+                        .cloned()
+                        .collect::<Vec<String>>()
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap_or_default())
+            .collect::<Vec<String>>()
+    });
+    if verbose {
+        eprintln!("{}", tr("serving_https", &[&serving.len()]));
+    }
+    Ok(serving)
+}
+
+/// Names found in certificates logged for the zone. Wildcard entries
+/// can't be analysed and are left out.
+fn from_ct_logs(client: &Client, zone: &str) -> Result<Vec<String>, Box<dyn Error>> {
+    let url = format!("https://crt.sh/?q=%25.{}&output=json", zone);
+    let entries: Vec<CtLogEntry> = client
+        .get(url)
+        .send()
+        .and_then(|response| response.error_for_status()?.json())
+        .map_err(|e| tr("ct_logs_failed", &[&e]))?;
+    let suffix = format!(".{}", zone);
+    Ok(entries
+        .iter()
+        .flat_map(|entry| entry.name_value.lines())
+        .map(|name| name.trim().to_lowercase())
+        .filter(|name| !name.starts_with("*.") && (name == zone || name.ends_with(&suffix)))
+        .collect())
+}
+
+fn from_wordlist(zone: &str, path: &str) -> Result<Vec<String>, Box<dyn Error>> {
+    let content = fs::read_to_string(path)?;
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|label| !label.is_empty() && !label.starts_with('#'))
+        .map(|label| format!("{}.{}", label.to_lowercase(), zone))
+        .collect())
+}
+
+/// Any HTTP answer, even an error page, means the name serves HTTPS.
+fn serves_https(client: &Client, name: &str) -> bool {
+    client.head(format!("https://{}/", name)).send().is_ok()
+}
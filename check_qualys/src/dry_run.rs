@@ -0,0 +1,66 @@
+use crate::i18n::tr;
+use crate::scheduler;
+use crate::{endpoint_data_url, request_url, Cli, Target, HTTP_TIMEOUT, NAGIOS_TIMEOUT};
+use std::error::Error;
+
+/// Print what a run would do: the polling plan, then the request URLs and
+/// resolved policy of every domain, inventory and command line merged.
+pub fn dry_run(cli: &Cli, targets: &[&Target], resumed: usize) -> Result<(), Box<dyn Error>> {
+    let budget = scheduler::budget(cli).as_secs();
+    let timeout = cli.timeout.unwrap_or(NAGIOS_TIMEOUT);
+    let polls = u32::from(cli.attemps) + u32::from(cli.dns_attempts);
+    let plan = tr(
+        "plan_polling",
+        &[
+            &scheduler::base_delay(cli, 0),
+            &scheduler::base_delay(cli, 1),
+            &scheduler::base_delay(cli, polls.saturating_sub(1)),
+            &(cli.jitter * 100.0),
+            &cli.attemps,
+            &cli.dns_attempts,
+            &budget,
+            &HTTP_TIMEOUT.as_secs(),
+        ],
+    );
+    println!("{}", plan);
+    if budget > timeout {
+        println!("{}", tr("budget_exceeded", &[&budget, &timeout]));
+    }
+    if cli.error_retries > 0 {
+        println!("{}", tr("plan_error_retries", &[&cli.error_retries]));
+    }
+    if cli.retries > 0 {
+        let retries = tr("plan_retries", &[&cli.retry_delay, &cli.retries]);
+        println!("{}", retries);
+    }
+    if resumed > 0 {
+        println!("{}", tr("plan_resumed", &[&resumed]));
+    }
+    for target in targets {
+        println!();
+        if target.tags.is_empty() {
+            println!("{}", target.domain);
+        } else {
+            println!("{} [{}]", target.domain, target.tags.join(", "));
+        }
+        println!("  GET {}", request_url(cli, target, cli.start_new)?);
+        // Polls after the first one don't start assessments anew, restarts do
+        if cli.start_new || cli.error_retries > 0 {
+            println!("  GET {}", request_url(cli, target, !cli.start_new)?);
+        }
+        if cli.endpoint_data {
+            let url = endpoint_data_url(cli, &target.domain, "<address>");
+            println!("  {}", tr("plan_endpoint_data", &[&url]));
+        }
+        let thresholds = tr(
+            "plan_thresholds",
+            &[&target.policy.warning, &target.policy.critical],
+        );
+        println!("  {}", thresholds);
+        let policy = format!("{:#?}", target.policy);
+        for line in policy.lines() {
+            println!("  {}", line);
+        }
+    }
+    Ok(())
+}
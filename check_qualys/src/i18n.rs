@@ -0,0 +1,1598 @@
+use clap::ValueEnum;
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+/// Language of the user-facing messages. Nagios state keywords and
+/// finding codes are never translated.
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum Lang {
+    #[default]
+    En,
+    Fr,
+    De,
+}
+
+static LANG: OnceLock<Lang> = OnceLock::new();
+
+pub fn set_lang(lang: Lang) {
+    let _ = LANG.set(lang);
+}
+
+/// Messages by key, in English, French and German. `{0}`, `{1}`... are
+/// replaced by the arguments given to `tr`.
+const CATALOG: &[(&str, [&str; 3])] = &[
+    (
+        "grade_below",
+        [
+            "grade {0} is below {1}",
+            "note {0} inférieure à {1}",
+            "Note {0} liegt unter {1}",
+        ],
+    ),
+    (
+        "not_trusted",
+        [
+            "certificate is not trusted",
+            "certificat non reconnu comme fiable",
+            "Zertifikat ist nicht vertrauenswürdig",
+        ],
+    ),
+    (
+        "name_mismatch",
+        [
+            "certificate does not match the domain name",
+            "le certificat ne correspond pas au nom de domaine",
+            "Zertifikat passt nicht zum Domainnamen",
+        ],
+    ),
+    (
+        "not_trusted_because",
+        [
+            "certificate is not trusted: {0}",
+            "certificat non reconnu comme fiable : {0}",
+            "Zertifikat ist nicht vertrauenswürdig: {0}",
+        ],
+    ),
+    (
+        "name_mismatch_names",
+        [
+            "certificate does not match the domain name, it is issued for {0}",
+            "le certificat ne correspond pas au nom de domaine, il est émis pour {0}",
+            "Zertifikat passt nicht zum Domainnamen, es ist ausgestellt für {0}",
+        ],
+    ),
+    (
+        "cert_changed",
+        [
+            "certificate changed {0} days before the previous one expired",
+            "certificat changé {0} jours avant l'expiration du précédent",
+            "Zertifikat {0} Tage vor Ablauf des vorherigen gewechselt",
+        ],
+    ),
+    (
+        "cert_expires",
+        [
+            "certificate expires in {0} days",
+            "le certificat expire dans {0} jours",
+            "Zertifikat läuft in {0} Tagen ab",
+        ],
+    ),
+    (
+        "wildcard_denied",
+        [
+            "wildcard certificate for {0}",
+            "certificat générique pour {0}",
+            "Wildcard-Zertifikat für {0}",
+        ],
+    ),
+    (
+        "missing_caa",
+        [
+            "no CAA record restricts which CAs may issue certificates",
+            "aucun enregistrement CAA ne restreint les AC autorisées à émettre des certificats",
+            "kein CAA-Eintrag beschränkt, welche CAs Zertifikate ausstellen dürfen",
+        ],
+    ),
+    (
+        "missing_sct",
+        [
+            "certificate comes without Signed Certificate Timestamps",
+            "certificat fourni sans horodatage de certificat signé (SCT)",
+            "Zertifikat wird ohne Signed Certificate Timestamps ausgeliefert",
+        ],
+    ),
+    (
+        "no_resumption",
+        [
+            "sessions are not resumed",
+            "les sessions ne sont pas reprises",
+            "Sitzungen werden nicht wiederaufgenommen",
+        ],
+    ),
+    (
+        "tickets_denied",
+        [
+            "session tickets are issued",
+            "des tickets de session sont émis",
+            "Sitzungstickets werden ausgestellt",
+        ],
+    ),
+    (
+        "insecure_renegotiation",
+        [
+            "insecure client-initiated renegotiation is supported",
+            "la renégociation non sécurisée à l'initiative du client est prise en charge",
+            "Unsichere vom Client initiierte Neuverhandlung wird unterstützt",
+        ],
+    ),
+    (
+        "no_secure_renegotiation",
+        [
+            "secure renegotiation is not supported",
+            "la renégociation sécurisée n'est pas prise en charge",
+            "Sichere Neuverhandlung wird nicht unterstützt",
+        ],
+    ),
+    (
+        "compression_enabled",
+        [
+            "TLS compression is enabled, vulnerable to CRIME",
+            "la compression TLS est activée, vulnérable à CRIME",
+            "TLS-Kompression ist aktiviert, anfällig für CRIME",
+        ],
+    ),
+    (
+        "no_fallback_scsv",
+        [
+            "no downgrade protection (TLS_FALLBACK_SCSV)",
+            "aucune protection contre la rétrogradation (TLS_FALLBACK_SCSV)",
+            "kein Downgrade-Schutz (TLS_FALLBACK_SCSV)",
+        ],
+    ),
+    (
+        "early_data_enabled",
+        [
+            "TLS 1.3 early data (0-RTT) is accepted",
+            "les données précoces TLS 1.3 (0-RTT) sont acceptées",
+            "TLS-1.3-Early-Data (0-RTT) wird akzeptiert",
+        ],
+    ),
+    (
+        "weak_curves",
+        [
+            "weak curves offered: {0}",
+            "courbes faibles proposées : {0}",
+            "Schwache Kurven angeboten: {0}",
+        ],
+    ),
+    (
+        "no_x25519",
+        [
+            "X25519 is not offered",
+            "X25519 n'est pas proposée",
+            "X25519 wird nicht angeboten",
+        ],
+    ),
+    (
+        "no_server_preference",
+        [
+            "the server doesn't enforce its TLS 1.2 cipher suite order",
+            "le serveur n'impose pas son ordre des suites de chiffrement TLS 1.2",
+            "Der Server erzwingt seine TLS-1.2-Cipher-Suite-Reihenfolge nicht",
+        ],
+    ),
+    (
+        "no_forward_secrecy",
+        [
+            "suites without forward secrecy: {0}",
+            "suites sans confidentialité persistante : {0}",
+            "Suites ohne Forward Secrecy: {0}",
+        ],
+    ),
+    (
+        "partial_forward_secrecy",
+        [
+            "forward secrecy not negotiated by every client",
+            "confidentialité persistante non négociée par tous les clients",
+            "Forward Secrecy nicht mit allen Clients ausgehandelt",
+        ],
+    ),
+    (
+        "weak_cert_key",
+        [
+            "certificate key as strong as {0} RSA bits, {1} required",
+            "clé du certificat équivalente à {0} bits RSA, {1} requis",
+            "Zertifikatsschlüssel entspricht {0} RSA-Bits, {1} erforderlich",
+        ],
+    ),
+    (
+        "weak_key_exchange",
+        [
+            "key exchange as strong as {0} RSA bits, {1} required",
+            "échange de clés équivalent à {0} bits RSA, {1} requis",
+            "Schlüsselaustausch entspricht {0} RSA-Bits, {1} erforderlich",
+        ],
+    ),
+    (
+        "static_rsa",
+        [
+            "static RSA key exchange without forward secrecy: {0}",
+            "échange de clés RSA statique sans confidentialité persistante : {0}",
+            "Statischer RSA-Schlüsselaustausch ohne Forward Secrecy: {0}",
+        ],
+    ),
+    (
+        "sweet32",
+        [
+            "64-bit block ciphers vulnerable to Sweet32: {0}",
+            "chiffrements par blocs de 64 bits vulnérables à Sweet32 : {0}",
+            "Für Sweet32 anfällige 64-Bit-Blockchiffren: {0}",
+        ],
+    ),
+    (
+        "export_suites",
+        [
+            "export cipher suites are accepted (FREAK)",
+            "des suites de chiffrement export sont acceptées (FREAK)",
+            "Export-Cipher-Suites werden akzeptiert (FREAK)",
+        ],
+    ),
+    (
+        "logjam",
+        [
+            "weak DH key exchange (Logjam)",
+            "échange de clés DH faible (Logjam)",
+            "Schwacher DH-Schlüsselaustausch (Logjam)",
+        ],
+    ),
+    (
+        "weak_dh",
+        [
+            "DH parameters of {0} bits, below 2048",
+            "paramètres DH de {0} bits, moins de 2048",
+            "DH-Parameter mit {0} Bit, unter 2048",
+        ],
+    ),
+    (
+        "openssl_ccs_vulnerable",
+        [
+            "vulnerable to OpenSSL CCS injection (CVE-2014-0224)",
+            "vulnérable à l'injection CCS d'OpenSSL (CVE-2014-0224)",
+            "Anfällig für OpenSSL-CCS-Injection (CVE-2014-0224)",
+        ],
+    ),
+    (
+        "unexpected_issuer",
+        [
+            "certificate issued by an unexpected CA: {0}",
+            "certificat émis par une AC inattendue : {0}",
+            "Zertifikat von einer unerwarteten CA ausgestellt: {0}",
+        ],
+    ),
+    (
+        "missing_endpoints",
+        [
+            "{0} endpoints instead of at least {1}",
+            "{0} points de terminaison au lieu d'au moins {1}",
+            "{0} Endpunkte statt mindestens {1}",
+        ],
+    ),
+    (
+        "inconsistent_grades",
+        [
+            "endpoints received different grades: {0}",
+            "les points de terminaison ont des notes différentes : {0}",
+            "Endpunkte erhielten unterschiedliche Noten: {0}",
+        ],
+    ),
+    (
+        "stale_report",
+        [
+            "report is {0} hours old, the limit is {1}",
+            "le rapport date de {0} heures, la limite est {1}",
+            "Bericht ist {0} Stunden alt, die Grenze ist {1}",
+        ],
+    ),
+    (
+        "stale_cache",
+        [
+            "cached report is {0} hours old, too old to be trusted past {1}",
+            "le rapport en cache date de {0} heures, au-delà de {1} il n'est plus fiable",
+            "zwischengespeicherter Bericht ist {0} Stunden alt, ab {1} nicht mehr verlässlich",
+        ],
+    ),
+    (
+        "assessment_duration",
+        [
+            "Assessment took {0}s",
+            "Analyse effectuée en {0}s",
+            "Analyse dauerte {0}s",
+        ],
+    ),
+    (
+        "tested_at",
+        [
+            "Report tested at {0}",
+            "Rapport testé le {0}",
+            "Bericht getestet am {0}",
+        ],
+    ),
+    (
+        "criteria_version",
+        [
+            "Rating criteria {0}, engine {1}",
+            "Critères de notation {0}, moteur {1}",
+            "Bewertungskriterien {0}, Engine {1}",
+        ],
+    ),
+    (
+        "api_down",
+        [
+            "SSL Labs API unavailable: {0}",
+            "API SSL Labs indisponible : {0}",
+            "SSL-Labs-API nicht erreichbar: {0}",
+        ],
+    ),
+    (
+        "no_endpoint",
+        ["No endpoint", "Aucun point de terminaison", "Kein Endpunkt"],
+    ),
+    (
+        "no_graded_endpoint",
+        [
+            "No endpoint graded: {0}",
+            "Aucun point de terminaison noté : {0}",
+            "Kein Endpunkt bewertet: {0}",
+        ],
+    ),
+    (
+        "endpoint_not_ready",
+        [
+            "Endpoint not ready",
+            "Point de terminaison pas prêt",
+            "Endpunkt nicht bereit",
+        ],
+    ),
+    (
+        "alert_title",
+        [
+            "SSL Labs {0} on {1}",
+            "SSL Labs {0} sur {1}",
+            "SSL Labs {0} auf {1}",
+        ],
+    ),
+    ("domain", ["Domain", "Domaine", "Domain"]),
+    ("grade", ["Grade", "Note", "Note"]),
+    ("status", ["Status", "Statut", "Status"]),
+    ("no_grade", ["none", "aucune", "keine"]),
+    (
+        "report_link",
+        ["SSL Labs report", "Rapport SSL Labs", "SSL-Labs-Bericht"],
+    ),
+    ("cert_issuer", ["Issuer: {0}", "Émetteur : {0}", "Aussteller: {0}"]),
+    ("cert_subject", ["Subject: {0}", "Sujet : {0}", "Inhaber: {0}"]),
+    (
+        "cert_serial",
+        ["Serial number: {0}", "Numéro de série : {0}", "Seriennummer: {0}"],
+    ),
+    (
+        "caa_records",
+        [
+            "CAA records of {0}: {1}",
+            "Enregistrements CAA de {0} : {1}",
+            "CAA-Einträge von {0}: {1}",
+        ],
+    ),
+    (
+        "no_caa",
+        [
+            "No CAA record",
+            "Aucun enregistrement CAA",
+            "Kein CAA-Eintrag",
+        ],
+    ),
+    (
+        "sct_sources",
+        [
+            "Certificate Transparency: {0}",
+            "Transparence des certificats : {0}",
+            "Certificate Transparency: {0}",
+        ],
+    ),
+    ("no_sct", ["no SCT", "aucun SCT", "kein SCT"]),
+    (
+        "sct_certificate",
+        [
+            "embedded in the certificate",
+            "intégré au certificat",
+            "im Zertifikat eingebettet",
+        ],
+    ),
+    (
+        "sct_ocsp",
+        [
+            "in the OCSP response",
+            "dans la réponse OCSP",
+            "in der OCSP-Antwort",
+        ],
+    ),
+    (
+        "sct_tls",
+        [
+            "in a TLS extension",
+            "dans une extension TLS",
+            "in einer TLS-Erweiterung",
+        ],
+    ),
+    ("protocols", ["Protocols: {0}", "Protocoles : {0}", "Protokolle: {0}"]),
+    (
+        "downgrade_protection",
+        [
+            "Downgrade protection: {0}",
+            "Protection contre la rétrogradation : {0}",
+            "Downgrade-Schutz: {0}",
+        ],
+    ),
+    (
+        "early_data",
+        [
+            "0-RTT early data: {0}",
+            "Données précoces 0-RTT : {0}",
+            "0-RTT-Early-Data: {0}",
+        ],
+    ),
+    (
+        "named_groups",
+        [
+            "Key exchange groups: {0}",
+            "Groupes d'échange de clés : {0}",
+            "Schlüsselaustauschgruppen: {0}",
+        ],
+    ),
+    (
+        "server_preference",
+        [
+            "Server cipher suite order (TLS 1.2): {0}",
+            "Ordre des suites imposé par le serveur (TLS 1.2) : {0}",
+            "Cipher-Suite-Reihenfolge des Servers (TLS 1.2): {0}",
+        ],
+    ),
+    ("yes", ["yes", "oui", "ja"]),
+    ("no", ["no", "non", "nein"]),
+    (
+        "openssl_ccs",
+        [
+            "OpenSSL CCS injection: {0}",
+            "Injection CCS d'OpenSSL : {0}",
+            "OpenSSL-CCS-Injection: {0}",
+        ],
+    ),
+    (
+        "ccs_not_vulnerable",
+        ["not vulnerable", "non vulnérable", "nicht anfällig"],
+    ),
+    (
+        "ccs_not_exploitable",
+        [
+            "vulnerable, not exploitable",
+            "vulnérable, non exploitable",
+            "anfällig, nicht ausnutzbar",
+        ],
+    ),
+    (
+        "ccs_exploitable",
+        [
+            "vulnerable and exploitable",
+            "vulnérable et exploitable",
+            "anfällig und ausnutzbar",
+        ],
+    ),
+    (
+        "session_resumption",
+        [
+            "Session resumption: {0}",
+            "Reprise de session : {0}",
+            "Sitzungswiederaufnahme: {0}",
+        ],
+    ),
+    ("resumption_disabled", ["disabled", "désactivée", "deaktiviert"]),
+    (
+        "resumption_not_accepted",
+        [
+            "IDs assigned but not accepted",
+            "identifiants attribués mais refusés",
+            "IDs vergeben, aber nicht akzeptiert",
+        ],
+    ),
+    ("resumption_enabled", ["enabled", "activée", "aktiviert"]),
+    (
+        "forward_secrecy",
+        [
+            "Forward secrecy: {0}",
+            "Confidentialité persistante : {0}",
+            "Forward Secrecy: {0}",
+        ],
+    ),
+    (
+        "fs_robust",
+        [
+            "with every client",
+            "avec tous les clients",
+            "mit allen Clients",
+        ],
+    ),
+    (
+        "fs_modern",
+        [
+            "with modern browsers",
+            "avec les navigateurs récents",
+            "mit aktuellen Browsern",
+        ],
+    ),
+    (
+        "fs_some",
+        [
+            "with some browsers",
+            "avec certains navigateurs",
+            "mit einigen Browsern",
+        ],
+    ),
+    (
+        "fs_none",
+        [
+            "no",
+            "non",
+            "nein",
+        ],
+    ),
+    (
+        "session_tickets",
+        [
+            "Session tickets: {0}",
+            "Tickets de session : {0}",
+            "Sitzungstickets: {0}",
+        ],
+    ),
+    ("tickets_unsupported", ["not supported", "non pris en charge", "nicht unterstützt"]),
+    ("tickets_supported", ["supported", "pris en charge", "unterstützt"]),
+    ("tickets_faulty", ["faulty", "défaillants", "fehlerhaft"]),
+    (
+        "renegotiation",
+        ["Renegotiation: {0}", "Renégociation : {0}", "Neuverhandlung: {0}"],
+    ),
+    (
+        "reneg_insecure",
+        [
+            "insecure client-initiated",
+            "non sécurisée à l'initiative du client",
+            "unsicher, vom Client initiiert",
+        ],
+    ),
+    ("reneg_secure", ["secure", "sécurisée", "sicher"]),
+    ("reneg_unsupported", ["not supported", "non prise en charge", "nicht unterstützt"]),
+    (
+        "wildcard_names",
+        [
+            "Wildcard names: {0}",
+            "Noms génériques : {0}",
+            "Wildcard-Namen: {0}",
+        ],
+    ),
+    (
+        "cert_alt_names",
+        [
+            "Alternative names: {0}",
+            "Noms alternatifs : {0}",
+            "Alternative Namen: {0}",
+        ],
+    ),
+    (
+        "budget_exceeded",
+        [
+            "polling may take up to {0}s, more than the {1}s timeout: lower --attemps or --dns-attempts",
+            "l'interrogation peut durer jusqu'à {0}s, plus que le délai de {1}s : réduire --attemps ou --dns-attempts",
+            "Abfragen kann bis zu {0}s dauern, länger als das Zeitlimit von {1}s: --attemps oder --dns-attempts verringern",
+        ],
+    ),
+    (
+        "plan_polling",
+        [
+            "Polling after {0}s, then {1}s, ... {2}s with {3}% jitter, {4} attemps and {5} DNS polls, up to {6}s with {7}s per request",
+            "Interrogation après {0}s, puis {1}s, ... {2}s avec {3}% de gigue, {4} tentatives et {5} interrogations DNS, jusqu'à {6}s avec {7}s par requête",
+            "Abfrage nach {0}s, dann {1}s, ... {2}s mit {3}% Jitter, {4} Versuchen und {5} DNS-Abfragen, bis zu {6}s mit {7}s pro Anfrage",
+        ],
+    ),
+    (
+        "plan_error_retries",
+        [
+            "Assessments ending in ERROR started anew up to {0} times",
+            "Analyses terminées en ERROR relancées jusqu'à {0} fois",
+            "Mit ERROR endende Analysen bis zu {0} Mal neu gestartet",
+        ],
+    ),
+    (
+        "plan_retries",
+        [
+            "Failed requests sent again after {0}s up to {1} times",
+            "Requêtes échouées renvoyées après {0}s jusqu'à {1} fois",
+            "Fehlgeschlagene Anfragen nach {0}s bis zu {1} Mal erneut gesendet",
+        ],
+    ),
+    (
+        "plan_resumed",
+        [
+            "{0} domains reported from the state file",
+            "{0} domaines rapportés depuis le fichier d'état",
+            "{0} Domains aus der Statusdatei gemeldet",
+        ],
+    ),
+    (
+        "plan_endpoint_data",
+        [
+            "GET {0} for each endpoint graded",
+            "GET {0} pour chaque endpoint noté",
+            "GET {0} für jeden bewerteten Endpunkt",
+        ],
+    ),
+    (
+        "plan_thresholds",
+        [
+            "WARNING below {0}, CRITICAL below {1}",
+            "WARNING sous {0}, CRITICAL sous {1}",
+            "WARNING unter {0}, CRITICAL unter {1}",
+        ],
+    ),
+    (
+        "attempts_exhausted",
+        [
+            "assessment still in progress after {0} attempts",
+            "analyse toujours en cours après {0} tentatives",
+            "Analyse nach {0} Versuchen noch nicht abgeschlossen",
+        ],
+    ),
+    (
+        "last_cached",
+        [
+            "assessment timed out, last result from {0}",
+            "délai d'analyse dépassé, dernier résultat du {0}",
+            "Zeitüberschreitung der Analyse, letztes Ergebnis vom {0}",
+        ],
+    ),
+    (
+        "api_status",
+        [
+            "the API replied with HTTP status {0}",
+            "l'API a répondu avec le statut HTTP {0}",
+            "die API antwortete mit HTTP-Status {0}",
+        ],
+    ),
+    (
+        "local_scan",
+        [
+            "local scan",
+            "analyse locale",
+            "lokale Analyse",
+        ],
+    ),
+    (
+        "local_scan_only",
+        [
+            "SSL Labs unavailable, protocols and certificate checked locally without a grade",
+            "SSL Labs indisponible, protocoles et certificat vérifiés localement sans note",
+            "SSL Labs nicht verfügbar, Protokolle und Zertifikat lokal ohne Note geprüft",
+        ],
+    ),
+    (
+        "local_not_trusted",
+        [
+            "certificate is not trusted: {0}",
+            "certificat non reconnu comme fiable : {0}",
+            "Zertifikat ist nicht vertrauenswürdig: {0}",
+        ],
+    ),
+    (
+        "no_handshake",
+        [
+            "no TLS handshake succeeded with {0}",
+            "aucune négociation TLS n'a abouti avec {0}",
+            "kein TLS-Handshake mit {0} erfolgreich",
+        ],
+    ),
+    (
+        "local_scan_failed",
+        [
+            "{0}, local scan failed: {1}",
+            "{0}, échec de l'analyse locale : {1}",
+            "{0}, lokale Analyse fehlgeschlagen: {1}",
+        ],
+    ),
+    (
+        "endpoint_grade",
+        [
+            "Endpoint {0}: {1}",
+            "Point de terminaison {0} : {1}",
+            "Endpunkt {0}: {1}",
+        ],
+    ),
+    (
+        "endpoint_server_grade",
+        [
+            "Endpoint {0} ({1}): {2}",
+            "Point de terminaison {0} ({1}) : {2}",
+            "Endpunkt {0} ({1}): {2}",
+        ],
+    ),
+    (
+        "not_graded",
+        [
+            "not graded",
+            "non noté",
+            "nicht bewertet",
+        ],
+    ),
+    (
+        "graded",
+        [
+            "{0} graded {1} (expected >= {2})",
+            "{0} noté {1} (attendu >= {2})",
+            "{0} mit {1} bewertet (erwartet >= {2})",
+        ],
+    ),
+    (
+        "unknown_state",
+        [
+            "unknown assessment status {0}",
+            "statut d'analyse inconnu {0}",
+            "unbekannter Analysestatus {0}",
+        ],
+    ),
+    (
+        "unknown_grade",
+        [
+            "unknown grade {0}",
+            "note inconnue {0}",
+            "unbekannte Note {0}",
+        ],
+    ),
+    (
+        "deadline_exceeded",
+        [
+            "assessment did not complete within {0}s",
+            "analyse non terminée en {0} s",
+            "Analyse nicht innerhalb von {0}s abgeschlossen",
+        ],
+    ),
+    (
+        "cert_key",
+        [
+            "Key: {0} {1} bits",
+            "Clé : {0} {1} bits",
+            "Schlüssel: {0} {1} Bit",
+        ],
+    ),
+    (
+        "cert_chain",
+        [
+            "Certificate chain: {0}",
+            "Chaîne de certificats : {0}",
+            "Zertifikatskette: {0}",
+        ],
+    ),
+    (
+        "cipher_suites",
+        [
+            "Cipher suites: {0}",
+            "Suites de chiffrement : {0}",
+            "Cipher-Suites: {0}",
+        ],
+    ),
+    (
+        "vulnerable",
+        [
+            "vulnerable to {0}",
+            "vulnérable à {0}",
+            "Anfällig für {0}",
+        ],
+    ),
+    (
+        "vulnerabilities",
+        [
+            "Vulnerabilities: {0}",
+            "Vulnérabilités : {0}",
+            "Schwachstellen: {0}",
+        ],
+    ),
+    (
+        "scores",
+        [
+            "Scores: certificate {0}, protocol support {1}, key exchange {2}, cipher strength {3}, overall {4}",
+            "Scores : certificat {0}, protocoles {1}, échange de clés {2}, chiffrement {3}, global {4}",
+            "Bewertungen: Zertifikat {0}, Protokolle {1}, Schlüsselaustausch {2}, Verschlüsselung {3}, gesamt {4}",
+        ],
+    ),
+    (
+        "forbidden_protocols",
+        [
+            "forbidden protocols offered: {0}",
+            "protocoles interdits proposés : {0}",
+            "Verbotene Protokolle angeboten: {0}",
+        ],
+    ),
+    (
+        "forbidden_ciphers",
+        [
+            "{0} forbidden cipher suites accepted",
+            "{0} suites de chiffrement interdites acceptées",
+            "{0} verbotene Cipher-Suites akzeptiert",
+        ],
+    ),
+    (
+        "forbidden_suites",
+        [
+            "Forbidden cipher suites: {0}",
+            "Suites de chiffrement interdites : {0}",
+            "Verbotene Cipher-Suites: {0}",
+        ],
+    ),
+    (
+        "hsts_missing",
+        [
+            "HSTS {0}",
+            "HSTS {0}",
+            "HSTS {0}",
+        ],
+    ),
+    (
+        "hsts_short",
+        [
+            "HSTS max-age of {0}s, below {1}s",
+            "max-age HSTS de {0} s, inférieur à {1} s",
+            "HSTS-max-age von {0} s, unter {1} s",
+        ],
+    ),
+    (
+        "hsts",
+        [
+            "HSTS: {0}",
+            "HSTS : {0}",
+            "HSTS: {0}",
+        ],
+    ),
+    (
+        "hsts_max_age",
+        [
+            "HSTS: max-age {0}s",
+            "HSTS : max-age {0} s",
+            "HSTS: max-age {0} s",
+        ],
+    ),
+    (
+        "cert_revoked",
+        [
+            "certificate revoked",
+            "certificat révoqué",
+            "Zertifikat widerrufen",
+        ],
+    ),
+    (
+        "no_ocsp_stapling",
+        [
+            "OCSP responses not stapled",
+            "réponses OCSP non agrafées",
+            "OCSP-Antworten nicht geheftet",
+        ],
+    ),
+    (
+        "revocation",
+        [
+            "Revocation: {0}",
+            "Révocation : {0}",
+            "Widerruf: {0}",
+        ],
+    ),
+    ("revoked", ["revoked", "révoqué", "widerrufen"]),
+    ("not_revoked", ["not revoked", "non révoqué", "nicht widerrufen"]),
+    (
+        "revocation_check_failed",
+        [
+            "check failed",
+            "échec de la vérification",
+            "Prüfung fehlgeschlagen",
+        ],
+    ),
+    (
+        "no_revocation_info",
+        [
+            "no revocation information",
+            "aucune information de révocation",
+            "keine Widerrufsinformationen",
+        ],
+    ),
+    (
+        "ocsp_stapling",
+        [
+            "OCSP stapling: {0}",
+            "Agrafage OCSP : {0}",
+            "OCSP-Stapling: {0}",
+        ],
+    ),
+    (
+        "chain_problems",
+        [
+            "certificate chain {0}",
+            "chaîne de certificats {0}",
+            "Zertifikatskette {0}",
+        ],
+    ),
+    (
+        "not_trusted_by",
+        [
+            "certificate is not trusted by {0}: {1}",
+            "certificat non reconnu comme fiable par {0} : {1}",
+            "Zertifikat ist für {0} nicht vertrauenswürdig: {1}",
+        ],
+    ),
+    (
+        "trust_store",
+        [
+            "Trusted by {0}",
+            "Reconnu comme fiable par {0}",
+            "Vertrauenswürdig für {0}",
+        ],
+    ),
+    (
+        "untrusted_store",
+        [
+            "Not trusted by {0}: {1}",
+            "Non reconnu comme fiable par {0} : {1}",
+            "Nicht vertrauenswürdig für {0}: {1}",
+        ],
+    ),
+    (
+        "cert_issues",
+        [
+            "Certificate issues: {0}",
+            "Problèmes de certificat : {0}",
+            "Zertifikatsprobleme: {0}",
+        ],
+    ),
+    (
+        "cert_no_chain",
+        [
+            "no chain of trust",
+            "aucune chaîne de confiance",
+            "keine Vertrauenskette",
+        ],
+    ),
+    (
+        "cert_not_yet_valid",
+        [
+            "not yet valid",
+            "pas encore valide",
+            "noch nicht gültig",
+        ],
+    ),
+    (
+        "cert_expired",
+        [
+            "expired",
+            "expiré",
+            "abgelaufen",
+        ],
+    ),
+    (
+        "cert_hostname_mismatch",
+        [
+            "for another host name",
+            "pour un autre nom d'hôte",
+            "für einen anderen Hostnamen",
+        ],
+    ),
+    (
+        "cert_revoked_issue",
+        [
+            "revoked",
+            "révoqué",
+            "widerrufen",
+        ],
+    ),
+    (
+        "cert_bad_common_name",
+        [
+            "with a bad common name",
+            "avec un nom commun invalide",
+            "mit ungültigem Common Name",
+        ],
+    ),
+    (
+        "cert_self_signed",
+        [
+            "self-signed",
+            "auto-signé",
+            "selbstsigniert",
+        ],
+    ),
+    (
+        "cert_blacklisted",
+        [
+            "blacklisted",
+            "sur liste noire",
+            "auf der Sperrliste",
+        ],
+    ),
+    (
+        "cert_insecure_signature",
+        [
+            "with an insecure signature",
+            "avec une signature non sûre",
+            "mit unsicherer Signatur",
+        ],
+    ),
+    (
+        "cert_insecure_key",
+        [
+            "with an insecure key",
+            "avec une clé non sûre",
+            "mit unsicherem Schlüssel",
+        ],
+    ),
+    (
+        "chain_issues",
+        [
+            "Chain issues: {0}",
+            "Problèmes de chaîne : {0}",
+            "Kettenprobleme: {0}",
+        ],
+    ),
+    (
+        "chain_incomplete",
+        [
+            "incomplete",
+            "incomplète",
+            "unvollständig",
+        ],
+    ),
+    (
+        "chain_extra_certs",
+        [
+            "with unrelated or duplicate certificates",
+            "avec des certificats superflus ou en double",
+            "mit fremden oder doppelten Zertifikaten",
+        ],
+    ),
+    (
+        "chain_order",
+        [
+            "in the wrong order",
+            "dans le mauvais ordre",
+            "in falscher Reihenfolge",
+        ],
+    ),
+    (
+        "chain_self_signed",
+        [
+            "with a self-signed root",
+            "avec une racine auto-signée",
+            "mit selbstsignierter Wurzel",
+        ],
+    ),
+    (
+        "chain_unvalidated",
+        [
+            "couldn't be validated",
+            "n'a pas pu être validée",
+            "konnte nicht validiert werden",
+        ],
+    ),
+    (
+        "invalid_header",
+        [
+            "invalid --header {0}: {1}",
+            "--header {0} invalide : {1}",
+            "Ungültiger --header {0}: {1}",
+        ],
+    ),
+    (
+        "invalid_tls_file",
+        [
+            "cannot use {0}: {1}",
+            "impossible d'utiliser {0} : {1}",
+            "{0} kann nicht verwendet werden: {1}",
+        ],
+    ),
+    (
+        "insecure",
+        [
+            "WARNING: --insecure, the certificate of the API isn't verified and the results can be forged",
+            "ATTENTION : --insecure, le certificat de l'API n'est pas vérifié et les résultats peuvent être falsifiés",
+            "WARNUNG: --insecure, das Zertifikat der API wird nicht geprüft und die Ergebnisse können gefälscht werden",
+        ],
+    ),
+    (
+        "invalid_proxy",
+        [
+            "invalid --proxy {0}: {1}",
+            "--proxy {0} invalide : {1}",
+            "Ungültiger --proxy {0}: {1}",
+        ],
+    ),
+    (
+        "email_required",
+        [
+            "API v4 requires --email",
+            "l'API v4 exige --email",
+            "API v4 erfordert --email",
+        ],
+    ),
+    (
+        "director_endpoint_overrides",
+        [
+            "{0}: endpoint overrides cannot be registered in the Director, check it with --inventory",
+            "{0} : les exceptions par endpoint ne peuvent pas être inscrites dans le Director, vérifiez-le avec --inventory",
+            "{0}: Endpoint-Ausnahmen können nicht im Director registriert werden, prüfen Sie ihn mit --inventory",
+        ],
+    ),
+    (
+        "notification_failed",
+        [
+            "Notification for {0} failed: {1}",
+            "La notification pour {0} a échoué : {1}",
+            "Benachrichtigung für {0} fehlgeschlagen: {1}",
+        ],
+    ),
+    (
+        "finding",
+        [
+            "Finding {0}: {1}",
+            "Constat {0} : {1}",
+            "Befund {0}: {1}",
+        ],
+    ),
+    (
+        "update_unverifiable",
+        [
+            "Version {0} is available at {1}, this build has no release key to verify it with: install it by hand",
+            "La version {0} est disponible sur {1}, cette version n'a pas de clé de publication pour la vérifier : installez-la à la main",
+            "Version {0} ist unter {1} verfügbar, dieser Build hat keinen Release-Schlüssel zur Prüfung: manuell installieren",
+        ],
+    ),
+    (
+        "bad_release_signature",
+        [
+            "{0} of release {1} doesn't bear the signature of the release key",
+            "{0} de la version {1} ne porte pas la signature de la clé de publication",
+            "{0} von Release {1} trägt nicht die Signatur des Release-Schlüssels",
+        ],
+    ),
+    (
+        "ct_logs_failed",
+        [
+            "crt.sh lookup failed, --no-ct-logs skips it: {0}",
+            "la recherche sur crt.sh a échoué, --no-ct-logs la saute : {0}",
+            "crt.sh-Abfrage fehlgeschlagen, --no-ct-logs überspringt sie: {0}",
+        ],
+    ),
+    (
+        "director_registered",
+        [
+            "Registered {0}",
+            "{0} inscrit",
+            "{0} registriert",
+        ],
+    ),
+    (
+        "director_registered_hosts",
+        [
+            "Registered {0} hosts",
+            "{0} hôtes inscrits",
+            "{0} Hosts registriert",
+        ],
+    ),
+    (
+        "release_asset_missing",
+        [
+            "release {0} has no {1} asset",
+            "la version {0} n'a pas de fichier {1}",
+            "Release {0} hat keine Datei {1}",
+        ],
+    ),
+    (
+        "update_available",
+        [
+            "Version {0} is available (running {1})",
+            "La version {0} est disponible (en cours : {1})",
+            "Version {0} ist verfügbar (laufend: {1})",
+        ],
+    ),
+    (
+        "up_to_date",
+        [
+            "Version {0} is up to date",
+            "La version {0} est à jour",
+            "Version {0} ist aktuell",
+        ],
+    ),
+    (
+        "checksum_missing",
+        [
+            "{0} isn't listed in {1}",
+            "{0} ne figure pas dans {1}",
+            "{0} ist nicht in {1} aufgeführt",
+        ],
+    ),
+    (
+        "checksum_mismatch",
+        [
+            "checksum mismatch for {0}: got {1}, expected {2}",
+            "somme de contrôle incorrecte pour {0} : {1} au lieu de {2}",
+            "Prüfsumme von {0} stimmt nicht: {1} statt {2}",
+        ],
+    ),
+    (
+        "updated",
+        [
+            "Updated from {0} to {1}",
+            "Mis à jour de {0} vers {1}",
+            "Aktualisiert von {0} auf {1}",
+        ],
+    ),
+    (
+        "discovered",
+        [
+            "Discovered {0} candidate subdomains",
+            "{0} sous-domaines candidats découverts",
+            "{0} mögliche Subdomains gefunden",
+        ],
+    ),
+    (
+        "serving_https",
+        [
+            "{0} subdomains serve HTTPS",
+            "{0} sous-domaines servent HTTPS",
+            "{0} Subdomains bieten HTTPS an",
+        ],
+    ),
+    (
+        "subject_inventory",
+        [
+            "inventory",
+            "inventaire",
+            "Inventar",
+        ],
+    ),
+    (
+        "subject_timeout",
+        [
+            "timeout",
+            "délai",
+            "Zeitlimit",
+        ],
+    ),
+    (
+        "subject_api",
+        [
+            "API",
+            "API",
+            "API",
+        ],
+    ),
+    (
+        "subject_state_file",
+        [
+            "state file",
+            "fichier d'état",
+            "Statusdatei",
+        ],
+    ),
+    (
+        "subject_audit_log",
+        [
+            "audit log",
+            "journal d'audit",
+            "Audit-Protokoll",
+        ],
+    ),
+    (
+        "selftest_hosts",
+        [
+            "{0} hosts in {1}",
+            "{0} hôtes dans {1}",
+            "{0} Hosts in {1}",
+        ],
+    ),
+    (
+        "selftest_invalid_domains",
+        [
+            "invalid domains in {0}: {1}",
+            "domaines invalides dans {0} : {1}",
+            "ungültige Domains in {0}: {1}",
+        ],
+    ),
+    (
+        "selftest_budget",
+        [
+            "polling may take up to {0}s out of {1}s",
+            "l'interrogation peut durer jusqu'à {0}s sur {1}s",
+            "Abfragen kann bis zu {0}s von {1}s dauern",
+        ],
+    ),
+    (
+        "selftest_unknown",
+        [
+            "unknown",
+            "inconnu",
+            "unbekannt",
+        ],
+    ),
+    (
+        "selftest_api",
+        [
+            "engine {0}, criteria {1}, {2} of {3} assessments running",
+            "moteur {0}, critères {1}, {2} analyses en cours sur {3}",
+            "Engine {0}, Kriterien {1}, {2} von {3} Analysen laufen",
+        ],
+    ),
+    (
+        "selftest_state_writable",
+        [
+            "{0} writable, {1} domains",
+            "{0} accessible en écriture, {1} domaines",
+            "{0} beschreibbar, {1} Domains",
+        ],
+    ),
+    (
+        "selftest_not_writable",
+        [
+            "{0} not writable: {1}",
+            "{0} non accessible en écriture : {1}",
+            "{0} nicht beschreibbar: {1}",
+        ],
+    ),
+    (
+        "selftest_writable",
+        [
+            "{0} writable",
+            "{0} accessible en écriture",
+            "{0} beschreibbar",
+        ],
+    ),
+    (
+        "min_grade_conflict",
+        [
+            "{0}: {1} sets min_grade along warning, critical or grade_map",
+            "{0} : {1} définit min_grade avec warning, critical ou grade_map",
+            "{0}: {1} setzt min_grade zusammen mit warning, critical oder grade_map",
+        ],
+    ),
+    (
+        "registration_refused",
+        [
+            "registration refused ({0}): {1}",
+            "inscription refusée ({0}) : {1}",
+            "Registrierung abgelehnt ({0}): {1}",
+        ],
+    ),
+    (
+        "registered",
+        [
+            "{0} registered",
+            "{0} inscrit",
+            "{0} registriert",
+        ],
+    ),
+    (
+        "combined",
+        [
+            "SSL {0} - {1} domains, {2}",
+            "SSL {0} - {1} domaines, {2}",
+            "SSL {0} - {1} Domains, {2}",
+        ],
+    ),
+    (
+        "no_family_endpoint",
+        [
+            "No {0} endpoint",
+            "Aucun point de terminaison {0}",
+            "Kein {0}-Endpunkt",
+        ],
+    ),
+    (
+        "grade_mapped",
+        [
+            "grade {0} is mapped to {1}",
+            "la note {0} correspond à {1}",
+            "Note {0} entspricht {1}",
+        ],
+    ),
+    (
+        "graded_mapped",
+        [
+            "{0} graded {1}",
+            "{0} noté {1}",
+            "{0} mit {1} bewertet",
+        ],
+    ),
+    (
+        "dns_stuck",
+        [
+            "stuck in DNS resolution after {0} polls",
+            "bloqué dans la résolution DNS après {0} interrogations",
+            "hängt nach {0} Abfragen in der DNS-Auflösung fest",
+        ],
+    ),
+    (
+        "phase_dns",
+        [
+            "waiting for DNS resolution",
+            "en attente de la résolution DNS",
+            "wartet auf die DNS-Auflösung",
+        ],
+    ),
+    (
+        "phase_preparing_report",
+        [
+            "preparing the report",
+            "préparation du rapport",
+            "Bericht wird erstellt",
+        ],
+    ),
+    (
+        "phase_retrieving_cert",
+        [
+            "retrieving the certificate",
+            "récupération du certificat",
+            "Zertifikat wird abgerufen",
+        ],
+    ),
+    (
+        "phase_protocol_intolerance",
+        [
+            "testing protocol version intolerance",
+            "test de l'intolérance aux versions de protocole",
+            "Intoleranz gegenüber Protokollversionen wird getestet",
+        ],
+    ),
+    (
+        "phase_protocols",
+        [
+            "testing the supported protocols",
+            "test des protocoles pris en charge",
+            "Unterstützte Protokolle werden getestet",
+        ],
+    ),
+    (
+        "phase_suites",
+        [
+            "testing the cipher suites",
+            "test des suites de chiffrement",
+            "Cipher-Suites werden getestet",
+        ],
+    ),
+    (
+        "phase_handshake_simulation",
+        [
+            "simulating client handshakes",
+            "simulation des négociations des clients",
+            "Client-Handshakes werden simuliert",
+        ],
+    ),
+    (
+        "phase_renegotiation",
+        [
+            "testing renegotiation",
+            "test de la renégociation",
+            "Neuverhandlung wird getestet",
+        ],
+    ),
+    (
+        "phase_session_resumption",
+        [
+            "testing session resumption",
+            "test de la reprise de session",
+            "Sitzungswiederaufnahme wird getestet",
+        ],
+    ),
+    (
+        "phase_vulnerabilities",
+        [
+            "testing known vulnerabilities",
+            "test des vulnérabilités connues",
+            "Bekannte Schwachstellen werden getestet",
+        ],
+    ),
+    (
+        "phase_http",
+        [
+            "testing the HTTP response",
+            "test de la réponse HTTP",
+            "HTTP-Antwort wird getestet",
+        ],
+    ),
+    (
+        "phase_ocsp_stapling",
+        [
+            "testing OCSP stapling",
+            "test de l'agrafage OCSP",
+            "OCSP-Stapling wird getestet",
+        ],
+    ),
+    (
+        "phase_caa",
+        [
+            "checking the CAA records",
+            "vérification des enregistrements CAA",
+            "CAA-Einträge werden geprüft",
+        ],
+    ),
+    (
+        "phase_alpn",
+        [
+            "testing protocol negotiation (NPN/ALPN)",
+            "test de la négociation de protocole (NPN/ALPN)",
+            "Protokollaushandlung (NPN/ALPN) wird getestet",
+        ],
+    ),
+    (
+        "phase_key_exchange",
+        [
+            "testing key exchange parameters",
+            "test des paramètres d'échange de clés",
+            "Schlüsselaustauschparameter werden getestet",
+        ],
+    ),
+];
+
+/// Translate a message of the catalog into the selected language.
+pub fn tr(key: &str, args: &[&dyn Display]) -> String {
+    let index = match LANG.get().copied().unwrap_or_default() {
+        Lang::En => 0,
+        Lang::Fr => 1,
+        Lang::De => 2,
+    };
+    let template = CATALOG
+        .iter()
+        .find(|(name, _)| *name == key)
+        .map_or(key, |(_, messages)| messages[index]);
+    // One pass over the template, placeholders within the arguments are
+    // left as they are
+    let mut message = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        message.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let arg = rest[1..]
+            .find('}')
+            .and_then(|end| Some((end, args.get(rest[1..end + 1].parse::<usize>().ok()?)?)));
+        match arg {
+            Some((end, arg)) => {
+                message.push_str(&arg.to_string());
+                rest = &rest[end + 2..];
+            }
+            None => {
+                message.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    message.push_str(rest);
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn placeholders() {
+        let cases: &[(&str, &[&dyn Display], &str)] = &[
+            ("{0} and {1}", &[&"a", &"b"], "a and b"),
+            ("{1} before {0}", &[&"a", &"b"], "b before a"),
+            ("{0}", &[&"{1}", &"b"], "{1}"),
+            ("{0} {2}", &[&"a"], "a {2}"),
+            ("{x} {", &[&"a"], "{x} {"),
+        ];
+        for (key, args, expected) in cases {
+            assert_eq!(tr(key, args), *expected, "{}", key);
+        }
+    }
+}
@@ -0,0 +1,264 @@
+use crate::i18n::tr;
+use crate::policy::{
+    AddressFamily, CurvePolicy, EndpointOverride, EndpointPolicy, GradeMap, Policy,
+    ProtocolVersion, TrustIssueState, TrustStore,
+};
+use crate::Grade;
+use serde::Deserialize;
+use std::error::Error;
+use std::fs;
+
+/// A YAML inventory listing the hosts of a batch run.
+///
+/// ```yaml
+/// hosts:
+///   - domain: www.example.com
+///     warning: A+
+///     critical: A
+///     expect_issuer: [Let's Encrypt]
+///     deny_wildcard: true
+///     forbid_protocols: [ssl3, tls1.0, tls1.1]
+///     forbid_ciphers: [RC4, 3DES]
+///     tags: [production]
+///   - domain: staging.example.com
+///     grade_map: "A+=0,A=0,A-=0,B=1,*=2"
+///     ignore: [trust]
+///     expect_endpoints: 2
+///     endpoint_policy: first
+///     address_family: ipv4
+///     tags: [staging]
+///   - domain: pos.example.com
+///     critical: A
+///     endpoints:
+///       - ip_address: 192.0.2.10
+///         critical: B
+///         warning: B
+/// ```
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Inventory {
+    pub hosts: Vec<Host>,
+}
+
+/// An inventory entry, any option left out falls back to the command line one.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Host {
+    pub domain: String,
+    pub warning: Option<Grade>,
+    pub critical: Option<Grade>,
+    /// Stands for `warning` and `critical`, as `--min-grade` does
+    pub min_grade: Option<Grade>,
+    pub grade_map: Option<GradeMap>,
+    pub ignore: Option<Vec<String>>,
+    pub expect_endpoints: Option<usize>,
+    pub endpoint_policy: Option<EndpointPolicy>,
+    pub address_family: Option<AddressFamily>,
+    pub require_consistent_grades: Option<bool>,
+    pub max_report_age: Option<u64>,
+    pub expiry_warning: Option<i64>,
+    pub expiry_critical: Option<i64>,
+    pub expect_issuer: Option<Vec<String>>,
+    pub deny_wildcard: Option<bool>,
+    pub require_sct: Option<bool>,
+    pub require_caa: Option<bool>,
+    pub require_resumption: Option<bool>,
+    pub deny_session_tickets: Option<bool>,
+    pub check_renegotiation: Option<bool>,
+    pub deny_compression: Option<bool>,
+    pub require_fallback_scsv: Option<bool>,
+    pub deny_0rtt: Option<bool>,
+    pub forbid_protocols: Option<Vec<ProtocolVersion>>,
+    pub curve_policy: Option<CurvePolicy>,
+    pub require_server_preference: Option<bool>,
+    pub deny_static_rsa: Option<bool>,
+    pub require_forward_secrecy: Option<bool>,
+    pub min_key_strength: Option<u32>,
+    pub forbid_ciphers: Option<Vec<String>>,
+    pub require_hsts: Option<u64>,
+    pub require_ocsp_stapling: Option<bool>,
+    pub check_chain: Option<bool>,
+    pub trust_store: Option<TrustStore>,
+    pub if_trust_issues: Option<TrustIssueState>,
+    pub strict: Option<bool>,
+    pub check_openssl_ccs: Option<bool>,
+    pub fail_on_vuln: Option<bool>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Overrides of the grade thresholds and ignores by IP address
+    #[serde(default)]
+    pub endpoints: Vec<EndpointOverride>,
+}
+
+impl Inventory {
+    pub fn load(path: &str) -> Result<Inventory, Box<dyn Error>> {
+        let content = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        let inventory: Inventory =
+            serde_yaml::from_str(&content).map_err(|e| format!("{}: {}", path, e))?;
+        // As on the command line, min_grade stands for the thresholds
+        if let Some(host) = inventory.hosts.iter().find(|host| {
+            host.min_grade.is_some()
+                && (host.warning.is_some() || host.critical.is_some() || host.grade_map.is_some())
+        }) {
+            return Err(tr("min_grade_conflict", &[&path, &host.domain]).into());
+        }
+        Ok(inventory)
+    }
+}
+
+impl Host {
+    /// The policy of the host. Thresholds of its own take over the grade
+    /// map of the command line, `min_grade` is never given along them.
+    pub fn policy(&self, defaults: &Policy) -> Policy {
+        let (warning, critical) = match &self.min_grade {
+            Some(min_grade) => (Some(min_grade.clone()), Some(min_grade.below())),
+            None => (self.warning.clone(), self.critical.clone()),
+        };
+        let grade_map = match (&self.grade_map, &warning, &critical) {
+            (Some(grade_map), _, _) => Some(grade_map.clone()),
+            (None, None, None) => defaults.grade_map.clone(),
+            _ => None,
+        };
+        Policy {
+            warning: warning.unwrap_or(defaults.warning.clone()),
+            critical: critical.unwrap_or(defaults.critical.clone()),
+            grade_map,
+            ignore: self.ignore.clone().unwrap_or(defaults.ignore.clone()),
+            expect_endpoints: self.expect_endpoints.or(defaults.expect_endpoints),
+            endpoint_policy: self.endpoint_policy.unwrap_or(defaults.endpoint_policy),
+            address_family: self.address_family.or(defaults.address_family),
+            require_consistent_grades: self
+                .require_consistent_grades
+                .unwrap_or(defaults.require_consistent_grades),
+            max_report_age: self.max_report_age.or(defaults.max_report_age),
+            warn_stale: defaults.warn_stale,
+            deny_wildcard: self.deny_wildcard.unwrap_or(defaults.deny_wildcard),
+            require_sct: self.require_sct.unwrap_or(defaults.require_sct),
+            require_caa: self.require_caa.unwrap_or(defaults.require_caa),
+            require_resumption: self
+                .require_resumption
+                .unwrap_or(defaults.require_resumption),
+            deny_session_tickets: self
+                .deny_session_tickets
+                .unwrap_or(defaults.deny_session_tickets),
+            check_renegotiation: self
+                .check_renegotiation
+                .unwrap_or(defaults.check_renegotiation),
+            deny_compression: self.deny_compression.unwrap_or(defaults.deny_compression),
+            require_fallback_scsv: self
+                .require_fallback_scsv
+                .unwrap_or(defaults.require_fallback_scsv),
+            deny_0rtt: self.deny_0rtt.unwrap_or(defaults.deny_0rtt),
+            forbid_protocols: self
+                .forbid_protocols
+                .clone()
+                .unwrap_or(defaults.forbid_protocols.clone()),
+            curve_policy: self.curve_policy.unwrap_or(defaults.curve_policy),
+            require_server_preference: self
+                .require_server_preference
+                .unwrap_or(defaults.require_server_preference),
+            deny_static_rsa: self.deny_static_rsa.unwrap_or(defaults.deny_static_rsa),
+            require_forward_secrecy: self
+                .require_forward_secrecy
+                .unwrap_or(defaults.require_forward_secrecy),
+            min_key_strength: self.min_key_strength.or(defaults.min_key_strength),
+            require_hsts: self.require_hsts.or(defaults.require_hsts),
+            check_chain: self.check_chain.unwrap_or(defaults.check_chain),
+            trust_store: self.trust_store.or(defaults.trust_store),
+            if_trust_issues: self.if_trust_issues.or(defaults.if_trust_issues),
+            require_ocsp_stapling: self
+                .require_ocsp_stapling
+                .unwrap_or(defaults.require_ocsp_stapling),
+            forbid_ciphers: self
+                .forbid_ciphers
+                .clone()
+                .unwrap_or(defaults.forbid_ciphers.clone()),
+            strict: self.strict.unwrap_or(defaults.strict),
+            check_openssl_ccs: self.check_openssl_ccs.unwrap_or(defaults.check_openssl_ccs),
+            fail_on_vuln: self.fail_on_vuln.unwrap_or(defaults.fail_on_vuln),
+            endpoint_overrides: self.endpoints.clone(),
+            expiry_warning: self.expiry_warning.or(defaults.expiry_warning),
+            expiry_critical: self.expiry_critical.or(defaults.expiry_critical),
+            expect_issuer: self
+                .expect_issuer
+                .clone()
+                .unwrap_or(defaults.expect_issuer.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::tests::policy;
+    use std::env;
+    use std::process;
+
+    fn host(yaml: &str) -> Host {
+        serde_yaml::from_str(&format!("domain: www.example.com\n{}", yaml)).unwrap()
+    }
+
+    #[test]
+    fn thresholds() {
+        let defaults = Policy {
+            grade_map: Some("A+=0,*=1".parse().unwrap()),
+            ..policy()
+        };
+        let cases: &[(&str, Grade, Grade, Option<&str>)] = &[
+            ("", Grade::A, Grade::AMinus, Some("A+=0,*=1")),
+            ("warning: B", Grade::B, Grade::AMinus, None),
+            ("critical: C", Grade::A, Grade::C, None),
+            ("min_grade: B", Grade::B, Grade::C, None),
+            ("grade_map: \"*=0\"", Grade::A, Grade::AMinus, Some("*=0")),
+            ("ignore: [trust]", Grade::A, Grade::AMinus, Some("A+=0,*=1")),
+        ];
+        for (yaml, warning, critical, grade_map) in cases {
+            let policy = host(yaml).policy(&defaults);
+            assert_eq!(policy.warning, *warning, "{}", yaml);
+            assert_eq!(policy.critical, *critical, "{}", yaml);
+            let map = policy.grade_map.as_ref().map(GradeMap::to_string);
+            assert_eq!(map.as_deref(), *grade_map, "{}", yaml);
+        }
+    }
+
+    #[test]
+    fn options() {
+        let defaults = Policy {
+            ignore: vec!["trust".to_string()],
+            expiry_warning: Some(30),
+            ..policy()
+        };
+        let policy = host("deny_wildcard: true\nexpiry_warning: 10").policy(&defaults);
+        assert!(policy.deny_wildcard);
+        assert_eq!(policy.expiry_warning, Some(10));
+        assert_eq!(policy.ignore, ["trust"]);
+        let policy =
+            host("ignore: []\nendpoints: [{ip_address: 192.0.2.1, warning: B}]").policy(&defaults);
+        assert!(policy.ignore.is_empty());
+        assert_eq!(policy.endpoint_overrides.len(), 1);
+    }
+
+    #[test]
+    fn min_grade_along_thresholds() {
+        let cases: &[(&str, bool)] = &[
+            ("min_grade: B", true),
+            ("min_grade: B\nwarning: A", false),
+            ("min_grade: B\ncritical: C", false),
+            ("min_grade: B\ngrade_map: \"*=0\"", false),
+            ("warning: A\ncritical: B", true),
+        ];
+        for (index, (yaml, valid)) in cases.iter().enumerate() {
+            let path =
+                env::temp_dir().join(format!("check_qualys-{}-{}.yaml", process::id(), index));
+            let entry = yaml.replace('\n', "\n    ");
+            fs::write(
+                &path,
+                format!("hosts:\n  - domain: www.example.com\n    {}\n", entry),
+            )
+            .unwrap();
+            let loaded = Inventory::load(path.to_str().unwrap());
+            fs::remove_file(&path).unwrap();
+            assert_eq!(loaded.is_ok(), *valid, "{}", yaml);
+        }
+    }
+}
@@ -0,0 +1,201 @@
+//! Reduced assessment performed from the poller itself, for when SSL Labs
+//! can't be relied upon. It tells which protocols the server accepts,
+//! the suite each one negotiates and whether the certificate is trusted
+//! and about to expire, but gives no grade.
+
+use crate::i18n::tr;
+use crate::policy::{self, Finding, Policy};
+use crate::{EndpointStatus, State, Status, Suite};
+use openssl::asn1::Asn1Time;
+use openssl::hash::MessageDigest;
+use openssl::nid::Nid;
+use openssl::ssl::{HandshakeError, SslConnector, SslMethod, SslStream, SslVerifyMode, SslVersion};
+use openssl::x509::X509;
+use std::error::Error;
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+const PORT: u16 = 443;
+
+/// Time given to connect and to each handshake.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Protocols probed, with their name in the API reports.
+const PROTOCOLS: [(SslVersion, &str); 5] = [
+    (SslVersion::SSL3, "SSL 3.0"),
+    (SslVersion::TLS1, "TLS 1.0"),
+    (SslVersion::TLS1_1, "TLS 1.1"),
+    (SslVersion::TLS1_2, "TLS 1.2"),
+    (SslVersion::TLS1_3, "TLS 1.3"),
+];
+
+/// Assess the first address of the domain, of the family the policy lets
+/// count if any, and evaluate the policy against what could be found out.
+pub fn assess(domain: &str, policy: &Policy) -> Result<Status, Box<dyn Error>> {
+    let address = (domain, PORT)
+        .to_socket_addrs()?
+        .find(|address| {
+            policy
+                .address_family
+                .is_none_or(|family| family.includes(&address.ip().to_string()))
+        })
+        .ok_or_else(|| tr("no_endpoint", &[]))?;
+    let mut status = Status {
+        ready: true,
+        status: State::Ready,
+        message: Some(tr("local_scan", &[])),
+        local_scan: true,
+        endpoints: vec![EndpointStatus {
+            ip_address: address.ip().to_string(),
+            server_name: None,
+            grade: None,
+            grade_trust_ignored: None,
+        }],
+        ..Default::default()
+    };
+    for (version, name) in PROTOCOLS {
+        if let Ok(stream) = probe(domain, &address, version) {
+            status.protocols.push(name.to_string());
+            if let Some(cipher) = stream.ssl().current_cipher() {
+                let name = cipher.standard_name().unwrap_or(cipher.name());
+                if !status.suites.iter().any(|suite| suite.name == name) {
+                    status.suites.push(Suite {
+                        name: name.to_string(),
+                        dh_p: None,
+                        cipher_strength: u16::try_from(cipher.bits().secret).ok(),
+                        kx_strength: None,
+                    });
+                }
+            }
+        }
+    }
+    if status.protocols.is_empty() {
+        return Err(tr("no_handshake", &[&address]).into());
+    }
+
+    let verification = verify(domain, &address)?;
+    if let Some(certificate) = &verification.certificate {
+        describe(&mut status, certificate)?;
+    }
+    let mut findings = policy.evaluate(&status);
+    if let Some(reason) = verification.untrusted {
+        if !policy.ignores("trust") {
+            findings.push(Finding {
+                code: "trust".to_string(),
+                exit_code: policy.if_trust_issues.map_or(2, |state| state.exit_code()),
+                message: tr("local_not_trusted", &[&reason]),
+            });
+        }
+    }
+    if !policy.ignores("local_scan") {
+        findings.push(Finding {
+            code: "local_scan".to_string(),
+            exit_code: 1,
+            message: tr("local_scan_only", &[]),
+        });
+    }
+    status.exit_code = policy::exit_code(&findings);
+    status.findings = findings;
+    Ok(status)
+}
+
+fn connect(address: &SocketAddr) -> Result<TcpStream, Box<dyn Error>> {
+    let stream = TcpStream::connect_timeout(address, TIMEOUT)?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    Ok(stream)
+}
+
+/// Handshake over a single protocol, whatever the certificate and suites.
+fn probe(
+    domain: &str,
+    address: &SocketAddr,
+    version: SslVersion,
+) -> Result<SslStream<TcpStream>, Box<dyn Error>> {
+    let mut builder = SslConnector::builder(SslMethod::tls_client())?;
+    builder.set_min_proto_version(Some(version))?;
+    builder.set_max_proto_version(Some(version))?;
+    // Old protocols and suites are what is being looked for
+    builder.set_cipher_list("ALL:@SECLEVEL=0")?;
+    builder.set_verify(SslVerifyMode::NONE);
+    let stream = builder.build().connect(domain, connect(address)?)?;
+    Ok(stream)
+}
+
+/// Certificate served by the domain.
+struct Verification {
+    certificate: Option<X509>,
+    /// Why the certificate isn't trusted, if it isn't
+    untrusted: Option<String>,
+}
+
+/// Handshake verifying the certificate, repeated without verification
+/// to get hold of the certificate when it isn't trusted.
+fn verify(domain: &str, address: &SocketAddr) -> Result<Verification, Box<dyn Error>> {
+    let connector = SslConnector::builder(SslMethod::tls_client())?.build();
+    match connector.connect(domain, connect(address)?) {
+        Ok(stream) => Ok(Verification {
+            certificate: stream.ssl().peer_certificate(),
+            untrusted: None,
+        }),
+        Err(e) => {
+            let reason = match &e {
+                HandshakeError::Failure(stream) => {
+                    stream.ssl().verify_result().error_string().to_string()
+                }
+                _ => e.to_string(),
+            };
+            let mut builder = SslConnector::builder(SslMethod::tls_client())?;
+            builder.set_verify(SslVerifyMode::NONE);
+            let stream = builder.build().connect(domain, connect(address)?)?;
+            Ok(Verification {
+                certificate: stream.ssl().peer_certificate(),
+                untrusted: Some(reason),
+            })
+        }
+    }
+}
+
+/// Fill the certificate fields in the format of the API reports.
+fn describe(status: &mut Status, certificate: &X509) -> Result<(), Box<dyn Error>> {
+    let epoch = Asn1Time::from_unix(0)?;
+    let lifetime = epoch.diff(certificate.not_after())?;
+    status.cert_not_after =
+        Some((i64::from(lifetime.days) * 86_400 + i64::from(lifetime.secs)) * 1000);
+    let digest = certificate.digest(MessageDigest::sha256())?;
+    status.cert_fingerprint = Some(digest.iter().map(|byte| format!("{:02x}", byte)).collect());
+    // The API lists the most specific attribute first
+    let mut issuer: Vec<String> = certificate
+        .issuer_name()
+        .entries()
+        .filter_map(|entry| {
+            let attribute = entry.object().nid().short_name().ok()?;
+            let value = entry.data().as_utf8().ok()?;
+            Some(format!("{}={}", attribute, value))
+        })
+        .collect();
+    issuer.reverse();
+    status.cert_issuer = Some(issuer.join(", "));
+    status.cert_common_names = certificate
+        .subject_name()
+        .entries_by_nid(Nid::COMMONNAME)
+        .filter_map(|entry| Some(entry.data().as_utf8().ok()?.to_string()))
+        .collect();
+    status.cert_alt_names = certificate
+        .subject_alt_names()
+        .map(|names| {
+            names
+                .iter()
+                .filter_map(|name| name.dnsname().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default();
+    status.cert_serial = Some(
+        certificate
+            .serial_number()
+            .to_bn()?
+            .to_hex_str()?
+            .to_string(),
+    );
+    Ok(())
+}
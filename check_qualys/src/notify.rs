@@ -0,0 +1,149 @@
+use crate::i18n::tr;
+use crate::output::ServiceState;
+use crate::{status_line, Cli, Status, Target};
+use reqwest::blocking::Client;
+use serde_json::json;
+use std::error::Error;
+
+/// Send notifications when the state of a domain changes.
+pub struct Notifier<'a> {
+    client: Client,
+    cli: &'a Cli,
+}
+
+impl Notifier<'_> {
+    pub fn new(cli: &Cli, client: Client) -> Notifier<'_> {
+        Notifier { client, cli }
+    }
+
+    /// `previous` is the exit code of the last run, if the state file knows it.
+    /// A domain it doesn't know yet is only notified when not OK, so that a
+    /// first run doesn't post a card for every healthy one. Every channel is
+    /// notified whatever fails with the others, the errors come together.
+    pub fn transition(
+        &self,
+        target: &Target,
+        status: &Status,
+        previous: Option<i32>,
+    ) -> Result<(), Box<dyn Error>> {
+        if previous.unwrap_or(0) == status.exit_code {
+            return Ok(());
+        }
+        let mut errors = Vec::new();
+        if let Some(key) = &self.cli.opsgenie_api_key {
+            if let Err(e) = self.opsgenie(key, target, status) {
+                errors.push(format!("Opsgenie: {}", e));
+            }
+        }
+        if let Some(url) = &self.cli.teams_webhook {
+            if let Err(e) = self.teams(url, target, status) {
+                errors.push(format!("Teams: {}", e));
+            }
+        }
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(errors.join("; ").into()),
+        }
+    }
+
+    /// Open an alert aliased after the domain, or close it once OK again.
+    fn opsgenie(&self, key: &str, target: &Target, status: &Status) -> Result<(), Box<dyn Error>> {
+        let state = ServiceState::from_exit_code(status.exit_code);
+        let base = format!("{}/v2/alerts", self.cli.opsgenie_url.trim_end_matches('/'));
+        let request = match state {
+            ServiceState::Ok => self
+                .client
+                .post(format!("{}/{}/close", base, target.domain))
+                .query(&[("identifierType", "alias")])
+                .json(&json!({
+                    "source": "check_qualys",
+                    "note": status_line(status, target),
+                })),
+            _ => {
+                let priority = match state {
+                    ServiceState::Critical => "P2",
+                    ServiceState::Warning => "P3",
+                    _ => "P4",
+                };
+                self.client.post(base).json(&json!({
+                    "message": tr("alert_title", &[&state, &target.domain]),
+                    "alias": target.domain,
+                    "description": status_line(status, target),
+                    "priority": priority,
+                    "tags": target.tags,
+                    "source": "check_qualys",
+                }))
+            }
+        };
+        request
+            .header("Authorization", format!("GenieKey {}", key))
+            .send()?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    /// Post an adaptive card to a Microsoft Teams incoming webhook.
+    fn teams(&self, url: &str, target: &Target, status: &Status) -> Result<(), Box<dyn Error>> {
+        let state = ServiceState::from_exit_code(status.exit_code);
+        let color = match state {
+            ServiceState::Ok => "Good",
+            ServiceState::Warning => "Warning",
+            ServiceState::Critical => "Attention",
+            ServiceState::Unknown => "Default",
+        };
+        let grade = status
+            .grade
+            .as_ref()
+            .map_or(tr("no_grade", &[]), |grade| grade.to_string());
+        let mut body = vec![
+            json!({
+                "type": "TextBlock",
+                "size": "Medium",
+                "weight": "Bolder",
+                "color": color,
+                "text": tr("alert_title", &[&state, &target.domain]),
+            }),
+            json!({
+                "type": "FactSet",
+                "facts": [
+                    {"title": tr("domain", &[]), "value": target.domain},
+                    {"title": tr("grade", &[]), "value": grade},
+                    {"title": tr("status", &[]), "value": status.status.to_string()},
+                ],
+            }),
+        ];
+        body.extend(status.findings.iter().map(|finding| {
+            json!({
+                "type": "TextBlock",
+                "wrap": true,
+                "text": format!("- {}", finding.message),
+            })
+        }));
+        let card = json!({
+            "type": "message",
+            "attachments": [{
+                "contentType": "application/vnd.microsoft.card.adaptive",
+                "content": {
+                    "$schema": "http://adaptivecards.io/schemas/adaptive-card.json",
+                    "type": "AdaptiveCard",
+                    "version": "1.4",
+                    "body": body,
+                    "actions": [{
+                        "type": "Action.OpenUrl",
+                        "title": tr("report_link", &[]),
+                        "url": format!(
+                            "https://www.ssllabs.com/ssltest/analyze.html?d={}&latest",
+                            target.domain
+                        ),
+                    }],
+                },
+            }],
+        });
+        self.client
+            .post(url)
+            .json(&card)
+            .send()?
+            .error_for_status()?;
+        Ok(())
+    }
+}
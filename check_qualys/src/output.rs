@@ -0,0 +1,224 @@
+//! Machine-readable output contract.
+//!
+//! Every JSON object printed by the plugin carries a `schema_version`.
+//! Within a major version fields are only ever added: none is removed,
+//! renamed or changes type, and enums only gain values, so consumers must
+//! ignore fields and values they don't know. Anything else bumps the major.
+
+use crate::policy::Finding;
+use crate::reason::{self, Reason};
+use crate::scores::Scores;
+use crate::state::StateFile;
+use crate::{policy, report_age, status_line, Grade, State, Status, Target};
+use clap::ValueEnum;
+use schemars::schema::RootSchema;
+use schemars::{schema_for, JsonSchema};
+use serde::Serialize;
+use std::fmt;
+
+pub const SCHEMA_VERSION: &str = "1.7";
+
+/// Structured formats whose JSON Schema can be printed.
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum SchemaFormat {
+    /// Objects printed by `--output ndjson`, and by `--output json` alone or in an array
+    Ndjson,
+    /// The `--state-file` document
+    State,
+}
+
+/// JSON Schema of a format, for consumers to validate their parsers
+/// against and CI to diff between releases.
+pub fn json_schema(format: SchemaFormat) -> RootSchema {
+    match format {
+        SchemaFormat::Ndjson => schema_for!(CheckResult),
+        SchemaFormat::State => schema_for!(StateFile),
+    }
+}
+
+/// Nagios service state, matching the plugin exit code.
+#[derive(Serialize, JsonSchema, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ServiceState {
+    /// Exit code 0
+    Ok,
+    /// Exit code 1
+    Warning,
+    /// Exit code 2
+    Critical,
+    /// Exit code 3, or any unexpected one
+    Unknown,
+}
+
+impl fmt::Display for ServiceState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let label = match self {
+            ServiceState::Ok => "OK",
+            ServiceState::Warning => "WARNING",
+            ServiceState::Critical => "CRITICAL",
+            ServiceState::Unknown => "UNKNOWN",
+        };
+        f.write_str(label)
+    }
+}
+
+impl ServiceState {
+    pub fn from_exit_code(exit_code: i32) -> ServiceState {
+        match exit_code {
+            0 => ServiceState::Ok,
+            1 => ServiceState::Warning,
+            2 => ServiceState::Critical,
+            _ => ServiceState::Unknown,
+        }
+    }
+}
+
+/// Progress of the assessment on the SSL Labs side.
+#[derive(Serialize, JsonSchema, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AssessmentStatus {
+    /// The API is resolving the domain name
+    Dns,
+    /// The assessment failed, see `message`
+    Error,
+    /// The assessment is still running
+    InProgress,
+    /// The assessment is complete
+    Ready,
+    /// No status has been received from the API
+    Unknown,
+}
+
+impl From<&State> for AssessmentStatus {
+    fn from(state: &State) -> Self {
+        match state {
+            State::Dns => AssessmentStatus::Dns,
+            State::Error => AssessmentStatus::Error,
+            State::InProgress => AssessmentStatus::InProgress,
+            State::Ready => AssessmentStatus::Ready,
+            State::Unknown => AssessmentStatus::Unknown,
+        }
+    }
+}
+
+/// Result of one domain.
+#[derive(Serialize, JsonSchema, Debug)]
+pub struct CheckResult {
+    pub schema_version: &'static str,
+    pub domain: String,
+    pub tags: Vec<String>,
+    pub state: ServiceState,
+    pub exit_code: i32,
+    pub status: AssessmentStatus,
+    /// Cause of the state, absent when it is OK
+    pub reason: Option<Reason>,
+    /// Letter grade as published by SSL Labs, e.g. `A+` or `T`
+    pub grade: Option<String>,
+    /// Nagios status line, without the perfdata
+    pub summary: String,
+    /// Endpoints reported by the API
+    pub endpoints: Vec<EndpointResult>,
+    pub perfdata: Perfdata,
+    pub findings: Vec<FindingResult>,
+    /// Error of the plugin itself
+    pub error: Option<String>,
+    /// Message from the API
+    pub message: Option<String>,
+    /// Whether the result comes from the local fallback scan rather than SSL Labs
+    pub local_scan: bool,
+    /// Version of the SSL Labs rating criteria the grade follows, e.g. 2009q
+    pub criteria_version: Option<String>,
+    /// Whether Signed Certificate Timestamps come with the certificate,
+    /// absent without the details of the report
+    pub sct: Option<bool>,
+    /// DNS CAA records of the domain, e.g. `0 issue "letsencrypt.org"`,
+    /// absent without the details of the report
+    pub caa_records: Option<Vec<String>>,
+}
+
+/// An IP address of the domain and the grade it received.
+#[derive(Serialize, JsonSchema, Debug)]
+pub struct EndpointResult {
+    pub ip_address: String,
+    /// Reverse DNS name of the address, absent when it has none
+    pub server_name: Option<String>,
+    /// Absent while the endpoint isn't graded
+    pub grade: Option<String>,
+}
+
+/// Values of the Nagios perfdata, absent when unknown.
+#[derive(Serialize, JsonSchema, Debug)]
+pub struct Perfdata {
+    /// Grade on a scale from 0 (M, T) to 100 (A+), absent for grades the plugin doesn't know
+    pub grade: Option<u8>,
+    /// Seconds spent polling the API
+    pub assessment_time: Option<u64>,
+    /// Seconds since the API completed the assessment
+    pub report_age: Option<i64>,
+    pub cert_days_remaining: Option<i64>,
+    /// Category scores out of 100, along full-detail reports
+    pub scores: Option<Scores>,
+}
+
+/// A reason for the state not being OK.
+#[derive(Serialize, JsonSchema, Debug)]
+pub struct FindingResult {
+    /// Stable identifier, the one `--ignore` accepts
+    pub code: String,
+    pub state: ServiceState,
+    pub message: String,
+}
+
+impl From<&Finding> for FindingResult {
+    fn from(finding: &Finding) -> Self {
+        FindingResult {
+            code: finding.code.clone(),
+            state: ServiceState::from_exit_code(finding.exit_code),
+            message: finding.message.clone(),
+        }
+    }
+}
+
+impl CheckResult {
+    pub fn new(status: &Status, target: &Target) -> CheckResult {
+        CheckResult {
+            schema_version: SCHEMA_VERSION,
+            domain: target.domain.clone(),
+            tags: target.tags.clone(),
+            state: ServiceState::from_exit_code(status.exit_code),
+            exit_code: status.exit_code,
+            status: AssessmentStatus::from(&status.status),
+            reason: reason::of_status(status),
+            grade: status.grade.as_ref().map(|grade| grade.to_string()),
+            summary: status_line(status, target),
+            endpoints: status
+                .endpoints
+                .iter()
+                .map(|endpoint| EndpointResult {
+                    ip_address: endpoint.ip_address.clone(),
+                    server_name: endpoint.server_name.clone(),
+                    grade: endpoint.grade.as_ref().map(|grade| grade.to_string()),
+                })
+                .collect(),
+            perfdata: Perfdata {
+                grade: status.grade.as_ref().and_then(Grade::score),
+                assessment_time: status.duration,
+                report_age: report_age(status),
+                cert_days_remaining: status.cert_not_after.map(policy::days_remaining),
+                scores: status.scores,
+            },
+            findings: status.findings.iter().map(FindingResult::from).collect(),
+            error: status.error.clone(),
+            message: status.message.clone(),
+            local_scan: status.local_scan,
+            criteria_version: status.criteria_version.clone(),
+            sct: status.sct.map(|sct| sct.0 != 0),
+            caa_records: status.caa_policy.as_ref().map(|caa| {
+                caa.caa_records
+                    .iter()
+                    .map(|record| record.to_string())
+                    .collect()
+            }),
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use crate::i18n::tr;
+
+/// Catalog key of the description of an endpoint `statusDetails` code.
+fn key(code: &str) -> Option<&'static str> {
+    Some(match code {
+        "PREPARING_REPORT" => "phase_preparing_report",
+        "RETRIEVING_CERT_V3__NO_SNI" | "RETRIEVING_CERT_V3__SNI_APEX" | "RETRIEVING_CERT_V3" => {
+            "phase_retrieving_cert"
+        }
+        "TESTING_PROTOCOL_INTOLERANCE_399"
+        | "TESTING_PROTOCOL_INTOLERANCE_400"
+        | "TESTING_PROTOCOL_INTOLERANCE_499"
+        | "TESTING_PROTOCOL_INTOLERANCE" => "phase_protocol_intolerance",
+        "TESTING_PROTOCOLS" | "TESTING_SSL2" | "TESTING_SSL3" | "TESTING_TLS10"
+        | "TESTING_TLS11" | "TESTING_TLS12" | "TESTING_TLS13" => "phase_protocols",
+        "TESTING_SUITES" | "TESTING_SUITES_DEPRECATED" | "TESTING_SUITES_BULK" => "phase_suites",
+        "TESTING_HANDSHAKE_SIMULATION" => "phase_handshake_simulation",
+        "TESTING_RENEGOTIATION" | "TESTING_STRICT_RI" => "phase_renegotiation",
+        "TESTING_SESSION_RESUMPTION" | "TESTING_SESSION_TICKETS" => "phase_session_resumption",
+        "TESTING_HEARTBLEED"
+        | "TESTING_HEARTBEAT"
+        | "TESTING_OPENSSL_CCS"
+        | "TESTING_TICKETBLEED"
+        | "TESTING_ROBOT"
+        | "TESTING_POODLE"
+        | "TESTING_POODLE_TLS"
+        | "TESTING_BEAST"
+        | "TESTING_CVE_2016_2107"
+        | "TESTING_DROWN"
+        | "TESTING_BLEICHENBACHER" => "phase_vulnerabilities",
+        "TESTING_HTTPS" | "TESTING_HSTS" | "TESTING_HPKP" => "phase_http",
+        "TESTING_OCSP_STAPLING" | "TESTING_OCSP_STAPLING_PRIME" => "phase_ocsp_stapling",
+        "TESTING_CAA" => "phase_caa",
+        "TESTING_NPN" | "TESTING_ALPN" => "phase_alpn",
+        "TESTING_EC_NAMED_CURVES" | "TESTING_ECDHE_PARAMETER_REUSE" | "TESTING_DH_PARAMETERS" => {
+            "phase_key_exchange"
+        }
+        _ => return None,
+    })
+}
+
+/// Describe the assessment phase of an endpoint. Codes unknown here fall
+/// back to the message of the API, then to the code itself.
+pub fn describe(code: &str, message: Option<&str>) -> String {
+    match key(code) {
+        Some(key) => tr(key, &[]),
+        None => message.unwrap_or(code).to_string(),
+    }
+}
@@ -0,0 +1,1358 @@
+use crate::i18n::tr;
+use crate::output::ServiceState;
+use crate::{
+    CaaPolicy, ChainIssues, EndpointStatus, Grade, NamedGroup, Sct, Status, StoreTrust, Suite,
+};
+use chrono::Utc;
+use clap::ValueEnum;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::net::IpAddr;
+use std::str::FromStr;
+
+/// Requirements on the groups offered for ECDHE key exchanges.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum CurvePolicy {
+    /// Any group is fine
+    #[default]
+    Any,
+    /// No curve below 256 bits
+    Secure,
+    /// No curve below 256 bits, X25519 offered
+    Modern,
+}
+
+/// Root store whose trust in the certificate chain counts, SSL Labs grades
+/// against Mozilla's.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum TrustStore {
+    Mozilla,
+    Apple,
+    Android,
+    Java,
+    Windows,
+}
+
+impl TrustStore {
+    /// Whether a root store as the API names it is this one.
+    pub fn is(&self, name: &str) -> bool {
+        format!("{:?}", self).eq_ignore_ascii_case(name)
+    }
+}
+
+/// State of certificates that aren't trusted or don't match the domain
+/// name, apart from how the rest of the configuration is graded.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum TrustIssueState {
+    Warning,
+    Critical,
+}
+
+impl TrustIssueState {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            TrustIssueState::Warning => 1,
+            TrustIssueState::Critical => 2,
+        }
+    }
+}
+
+/// Endpoint whose grade stands for the host.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum EndpointPolicy {
+    /// The worst graded endpoint
+    #[default]
+    Worst,
+    /// The best graded endpoint
+    Best,
+    /// The first endpoint reported by the API
+    First,
+}
+
+/// Exit code of every grade, overriding the thresholds, e.g.
+/// `A+=0,A=0,A-=1,B=1,*=2` where `*` stands for the grades left out.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(try_from = "String")]
+pub struct GradeMap {
+    codes: Vec<(Grade, i32)>,
+    others: Option<i32>,
+}
+
+impl GradeMap {
+    pub fn exit_code(&self, grade: &Grade) -> i32 {
+        self.codes
+            .iter()
+            .find(|(mapped, _)| mapped == grade)
+            .map(|(_, exit_code)| *exit_code)
+            .or(self.others)
+            .unwrap_or(3)
+    }
+}
+
+impl FromStr for GradeMap {
+    type Err = String;
+
+    fn from_str(map: &str) -> Result<Self, Self::Err> {
+        let mut grade_map = GradeMap {
+            codes: Vec::new(),
+            others: None,
+        };
+        for entry in map
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+        {
+            let (grade, exit_code) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("{}: GRADE=EXIT_CODE expected", entry))?;
+            let exit_code = match exit_code.trim().parse::<i32>() {
+                Ok(exit_code) if (0..=3).contains(&exit_code) => exit_code,
+                _ => return Err(format!("{}: exit code from 0 to 3 expected", entry)),
+            };
+            match grade.trim() {
+                "*" => grade_map.others = Some(exit_code),
+                grade => {
+                    let grade = Grade::from_str(grade)
+                        .map_err(|_| format!("{}: unknown grade {}", entry, grade))?;
+                    grade_map.codes.push((grade, exit_code));
+                }
+            }
+        }
+        if grade_map.others.is_none() {
+            let missing: Vec<String> = Grade::ALL
+                .iter()
+                .filter(|grade| !grade_map.codes.iter().any(|(mapped, _)| mapped == *grade))
+                .map(Grade::to_string)
+                .collect();
+            if !missing.is_empty() {
+                return Err(format!(
+                    "no exit code for {} (*=EXIT_CODE maps the grades left out)",
+                    missing.join(", ")
+                ));
+            }
+        }
+        Ok(grade_map)
+    }
+}
+
+/// The map as `--grade-map` takes it.
+impl fmt::Display for GradeMap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut entries: Vec<String> = self
+            .codes
+            .iter()
+            .map(|(grade, exit_code)| format!("{}={}", grade, exit_code))
+            .collect();
+        entries.extend(self.others.map(|exit_code| format!("*={}", exit_code)));
+        f.write_str(&entries.join(","))
+    }
+}
+
+impl TryFrom<String> for GradeMap {
+    type Error = String;
+
+    fn try_from(map: String) -> Result<Self, Self::Error> {
+        GradeMap::from_str(&map)
+    }
+}
+
+/// Address family of the endpoints that count, for hosts that deliberately
+/// don't serve the other one.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AddressFamily {
+    Ipv4,
+    Ipv6,
+}
+
+impl AddressFamily {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AddressFamily::Ipv4 => "IPv4",
+            AddressFamily::Ipv6 => "IPv6",
+        }
+    }
+
+    /// Whether an endpoint address is of the family, those that can't be
+    /// parsed are kept.
+    pub fn includes(&self, address: &str) -> bool {
+        match address.parse::<IpAddr>() {
+            Ok(IpAddr::V4(_)) => *self == AddressFamily::Ipv4,
+            Ok(IpAddr::V6(_)) => *self == AddressFamily::Ipv6,
+            Err(_) => true,
+        }
+    }
+}
+
+/// Protocol versions as named on the command line and in inventories.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, ValueEnum)]
+pub enum ProtocolVersion {
+    #[serde(rename = "ssl2")]
+    #[value(name = "ssl2")]
+    Ssl2,
+    #[serde(rename = "ssl3")]
+    #[value(name = "ssl3")]
+    Ssl3,
+    #[serde(rename = "tls1.0")]
+    #[value(name = "tls1.0")]
+    Tls1_0,
+    #[serde(rename = "tls1.1")]
+    #[value(name = "tls1.1")]
+    Tls1_1,
+    #[serde(rename = "tls1.2")]
+    #[value(name = "tls1.2")]
+    Tls1_2,
+    #[serde(rename = "tls1.3")]
+    #[value(name = "tls1.3")]
+    Tls1_3,
+}
+
+impl ProtocolVersion {
+    /// Name of the protocol in the API reports.
+    pub fn api_name(&self) -> &'static str {
+        match self {
+            ProtocolVersion::Ssl2 => "SSL 2.0",
+            ProtocolVersion::Ssl3 => "SSL 3.0",
+            ProtocolVersion::Tls1_0 => "TLS 1.0",
+            ProtocolVersion::Tls1_1 => "TLS 1.1",
+            ProtocolVersion::Tls1_2 => "TLS 1.2",
+            ProtocolVersion::Tls1_3 => "TLS 1.3",
+        }
+    }
+}
+
+/// Thresholds and ignores applied to an assessment to decide its exit code.
+#[derive(Clone, Debug)]
+pub struct Policy {
+    /// Grades below this one raise a WARNING
+    pub warning: Grade,
+    /// Grades below this one raise a CRITICAL
+    pub critical: Grade,
+    /// Exit code of every grade, the thresholds are left aside when there is one
+    pub grade_map: Option<GradeMap>,
+    /// Finding codes that never contribute to the exit code
+    pub ignore: Vec<String>,
+    /// Endpoint whose grade is compared against the thresholds
+    pub endpoint_policy: EndpointPolicy,
+    /// Only endpoints of this family count, all of them when none
+    pub address_family: Option<AddressFamily>,
+    /// Least number of endpoints the API should report
+    pub expect_endpoints: Option<usize>,
+    /// Whether all the endpoints must receive the same grade
+    pub require_consistent_grades: bool,
+    /// Hours after which a report is too old to be trusted
+    pub max_report_age: Option<u64>,
+    /// Hours after which a report delivered from the cache is stale
+    pub warn_stale: Option<u64>,
+    /// Days before the certificate expiry that raise a WARNING
+    pub expiry_warning: Option<i64>,
+    /// Days before the certificate expiry that raise a CRITICAL
+    pub expiry_critical: Option<i64>,
+    /// Issuing CAs the certificate may come from, any when empty
+    pub expect_issuer: Vec<String>,
+    /// Whether wildcard certificates are forbidden
+    pub deny_wildcard: bool,
+    /// Whether the certificate must come with Signed Certificate Timestamps
+    pub require_sct: bool,
+    /// Whether CAA records must restrict the CAs that may issue certificates
+    pub require_caa: bool,
+    /// Whether the server must resume sessions from their ID
+    pub require_resumption: bool,
+    /// Whether session tickets are forbidden
+    pub deny_session_tickets: bool,
+    /// Whether renegotiation support is checked regardless of the grade
+    pub check_renegotiation: bool,
+    /// Whether TLS compression is forbidden
+    pub deny_compression: bool,
+    /// Whether servers offering protocols older than TLS 1.3 must prevent downgrades
+    pub require_fallback_scsv: bool,
+    /// Whether TLS 1.3 early data is forbidden
+    pub deny_0rtt: bool,
+    /// Protocol versions the server mustn't offer
+    pub forbid_protocols: Vec<ProtocolVersion>,
+    /// Requirements on the groups offered for key exchanges
+    pub curve_policy: CurvePolicy,
+    /// Whether the server must enforce its own TLS 1.2 cipher suite order
+    pub require_server_preference: bool,
+    /// Whether suites with a static RSA key exchange are forbidden
+    pub deny_static_rsa: bool,
+    /// Whether every suite and client must negotiate forward secrecy
+    pub require_forward_secrecy: bool,
+    /// Least strength, as an RSA key size, of the certificate key and key exchanges
+    pub min_key_strength: Option<u32>,
+    /// Whether problems of the certificate chain are checked
+    pub check_chain: bool,
+    /// Root store whose trust decides instead of the grade whether the
+    /// certificate is trusted
+    pub trust_store: Option<TrustStore>,
+    /// State of trust issues and name mismatches, those of their grade when none
+    pub if_trust_issues: Option<TrustIssueState>,
+    /// Whether the server must staple OCSP responses
+    pub require_ocsp_stapling: bool,
+    /// Least HSTS max-age in seconds, the header isn't required when none
+    pub require_hsts: Option<u64>,
+    /// Parts of cipher suite names, e.g. `RC4` or `CBC`, that aren't accepted
+    pub forbid_ciphers: Vec<String>,
+    /// Whether weak cipher findings are CRITICAL rather than WARNING
+    pub strict: bool,
+    /// Whether the OpenSSL CCS injection vulnerability is checked
+    pub check_openssl_ccs: bool,
+    /// Whether any known vulnerability is CRITICAL regardless of the grade
+    pub fail_on_vuln: bool,
+    /// Thresholds and ignores of specific endpoints of the host
+    pub endpoint_overrides: Vec<EndpointOverride>,
+}
+
+/// Grade thresholds and ignores of an endpoint that differ from those of
+/// its host, e.g. a legacy address kept for old clients.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EndpointOverride {
+    pub ip_address: String,
+    pub warning: Option<Grade>,
+    pub critical: Option<Grade>,
+    pub ignore: Option<Vec<String>>,
+}
+
+/// A single reason for the assessment not being OK.
+#[derive(Clone, Serialize, Deserialize, JsonSchema, Debug)]
+pub struct Finding {
+    pub code: String,
+    pub exit_code: i32,
+    pub message: String,
+}
+
+impl Policy {
+    /// Whether the checks need the endpoint details of full-detail reports.
+    pub fn needs_details(&self) -> bool {
+        self.expiry_warning.is_some()
+            || self.expiry_critical.is_some()
+            || !self.expect_issuer.is_empty()
+            || self.deny_wildcard
+            || self.require_sct
+            || self.require_caa
+            || self.require_resumption
+            || self.deny_session_tickets
+            || self.check_renegotiation
+            || self.deny_compression
+            || self.require_fallback_scsv
+            || self.deny_0rtt
+            || !self.forbid_protocols.is_empty()
+            || self.curve_policy != CurvePolicy::Any
+            || self.require_server_preference
+            || self.deny_static_rsa
+            || self.require_forward_secrecy
+            || self.min_key_strength.is_some()
+            || self.require_hsts.is_some()
+            || self.require_ocsp_stapling
+            || self.check_chain
+            || self.trust_store.is_some()
+            || !self.forbid_ciphers.is_empty()
+            || self.strict
+            || self.check_openssl_ccs
+            || self.fail_on_vuln
+    }
+
+    pub fn ignores(&self, code: &str) -> bool {
+        self.ignore
+            .iter()
+            .any(|ignored| ignored.eq_ignore_ascii_case(code))
+    }
+
+    /// Every finding of a complete assessment.
+    pub fn evaluate(&self, status: &Status) -> Vec<Finding> {
+        let mut findings = vec![];
+        if !self.endpoint_overrides.is_empty() {
+            findings.extend(self.check_endpoint_grades(&status.endpoints));
+        } else if let Some(grade) = &status.grade {
+            match self.store_trust(&status.trust_stores) {
+                // Only another store than the one chosen distrusts the chain
+                Some(trust) if trust.trusted && *grade == Grade::T => {
+                    if let Some(grade) = &status.grade_trust_ignored {
+                        findings.extend(self.check_grade(grade, None));
+                    }
+                }
+                _ => findings.extend(
+                    self.check_grade(grade, status.grade_trust_ignored.as_ref())
+                        .into_iter()
+                        .map(|finding| explain(finding, status)),
+                ),
+            }
+            findings.extend(self.check_trust_store(grade, &status.trust_stores));
+        }
+        findings.extend(self.check_endpoints(&status.endpoints));
+        findings.extend(self.check_report_age(status.test_time));
+        findings.extend(self.check_stale_cache(status.test_time));
+        findings.extend(self.check_issuer(status.cert_issuer.as_deref()));
+        findings.extend(self.check_expiry(status.cert_not_after));
+        findings.extend(self.check_wildcard(&status.wildcard_names()));
+        findings.extend(self.check_sct(status.sct));
+        findings.extend(self.check_caa(status.caa_policy.as_ref()));
+        findings.extend(self.check_revocation(status.cert_revocation_status));
+        findings.extend(self.check_chain(status.chain_issues));
+        findings.extend(self.check_ocsp_stapling(status.ocsp_stapling));
+        findings.extend(self.check_sessions(status.session_resumption, status.session_tickets));
+        findings.extend(self.check_renegotiation(status.reneg_support));
+        findings.extend(self.check_compression(status.compression_methods));
+        findings.extend(self.check_fallback_scsv(&status.protocols, status.fallback_scsv));
+        findings.extend(self.check_forbidden_protocols(&status.protocols));
+        findings.extend(self.check_0rtt(status.zero_rtt));
+        findings.extend(self.check_curves(&status.named_groups));
+        findings.extend(self.check_server_preference(status.server_preference));
+        findings.extend(self.check_static_rsa(&status.suites));
+        findings.extend(self.check_forward_secrecy(status.forward_secrecy, &status.suites));
+        findings.extend(self.check_key_strength(status.cert_key_strength, &status.suites));
+        findings.extend(self.check_forbidden_ciphers(&status.suites));
+        findings.extend(self.check_hsts(status.hsts.as_deref(), status.hsts_max_age));
+        findings.extend(self.check_sweet32(&status.suites));
+        findings.extend(self.check_export(&status.suites, status.freak, status.logjam));
+        findings.extend(self.check_openssl_ccs(status.openssl_ccs));
+        findings.extend(self.check_vulnerabilities(&status.vulnerabilities));
+        findings
+    }
+
+    /// Compare a grade against the thresholds. Ignoring `trust` grades
+    /// the endpoint as if its certificate were trusted, which is what
+    /// staging hosts signed by an internal CA need.
+    pub fn check_grade(&self, grade: &Grade, trust_ignored: Option<&Grade>) -> Vec<Finding> {
+        let (code, grade) = match grade {
+            Grade::T if self.ignores("trust") => match trust_ignored {
+                Some(trust_ignored) => ("grade", trust_ignored),
+                None => return vec![],
+            },
+            Grade::T => ("trust", grade),
+            Grade::M => ("mismatch", grade),
+            Grade::Other(_) => ("unknown_grade", grade),
+            _ => ("grade", grade),
+        };
+        let (exit_code, threshold) = match (&self.grade_map, self.if_trust_issues) {
+            (_, Some(state)) if matches!(code, "trust" | "mismatch") => (state.exit_code(), None),
+            (Some(map), _) => match map.exit_code(grade) {
+                0 => return vec![],
+                exit_code => (exit_code, None),
+            },
+            // There is no telling where it stands against the thresholds
+            (None, _) if code == "unknown_grade" => (1, None),
+            (None, _) if *grade < self.critical => (2, Some(&self.critical)),
+            (None, _) if *grade < self.warning => (1, Some(&self.warning)),
+            (None, _) => return vec![],
+        };
+        if self.ignores(code) {
+            return vec![];
+        }
+
+        let message = match (code, threshold) {
+            ("trust", _) => tr("not_trusted", &[]),
+            ("mismatch", _) => tr("name_mismatch", &[]),
+            ("unknown_grade", None) if self.grade_map.is_none() => tr("unknown_grade", &[grade]),
+            (_, Some(threshold)) => tr("grade_below", &[grade, threshold]),
+            (_, None) => {
+                let state = ServiceState::from_exit_code(exit_code);
+                tr("grade_mapped", &[grade, &state])
+            }
+        };
+        vec![Finding {
+            code: code.to_string(),
+            exit_code,
+            message,
+        }]
+    }
+
+    /// With overrides, every endpoint is graded against its own thresholds
+    /// rather than the host being graded from its first endpoint.
+    pub fn check_endpoint_grades(&self, endpoints: &[EndpointStatus]) -> Vec<Finding> {
+        let mut findings = vec![];
+        for endpoint in endpoints {
+            let Some(grade) = &endpoint.grade else {
+                continue;
+            };
+            let policy = self.for_endpoint(&endpoint.ip_address);
+            for mut finding in policy.check_grade(grade, endpoint.grade_trust_ignored.as_ref()) {
+                finding.message = format!("{} ({})", finding.message, endpoint.ip_address);
+                findings.push(finding);
+            }
+        }
+        findings
+    }
+
+    /// State of an endpoint from its grade alone, none while it isn't graded.
+    pub fn endpoint_exit_code(&self, endpoint: &EndpointStatus) -> Option<i32> {
+        let grade = endpoint.grade.as_ref()?;
+        let policy = self.for_endpoint(&endpoint.ip_address);
+        let findings = policy.check_grade(grade, endpoint.grade_trust_ignored.as_ref());
+        Some(exit_code(&findings))
+    }
+
+    /// The policy of the host with the overrides of an endpoint applied,
+    /// thresholds of its own take over the grade map.
+    pub fn for_endpoint(&self, ip_address: &str) -> Policy {
+        let mut policy = self.clone();
+        let overrides = self
+            .endpoint_overrides
+            .iter()
+            .filter(|endpoint| endpoint.ip_address == ip_address);
+        for endpoint in overrides {
+            if let Some(warning) = &endpoint.warning {
+                policy.warning = warning.clone();
+                policy.grade_map = None;
+            }
+            if let Some(critical) = &endpoint.critical {
+                policy.critical = critical.clone();
+                policy.grade_map = None;
+            }
+            if let Some(ignore) = &endpoint.ignore {
+                policy.ignore = ignore.clone();
+            }
+        }
+        policy
+    }
+
+    /// An IP address silently dropped out of DNS shows up as a missing
+    /// endpoint, a backend left out of a TLS rollout as a different grade.
+    pub fn check_endpoints(&self, endpoints: &[EndpointStatus]) -> Vec<Finding> {
+        let mut findings = vec![];
+        let count = endpoints.len();
+        match self.expect_endpoints {
+            Some(expected) if count < expected && !self.ignores("endpoints") => {
+                findings.push(Finding {
+                    code: "endpoints".to_string(),
+                    exit_code: 1,
+                    message: tr("missing_endpoints", &[&count, &expected]),
+                })
+            }
+            _ => (),
+        }
+
+        let graded: Vec<(&str, &Grade)> = endpoints
+            .iter()
+            .filter_map(|endpoint| Some((endpoint.ip_address.as_str(), endpoint.grade.as_ref()?)))
+            .collect();
+        let consistent = graded.windows(2).all(|pair| pair[0].1 == pair[1].1);
+        if self.require_consistent_grades && !consistent && !self.ignores("inconsistent_grades") {
+            let grades: Vec<String> = graded
+                .iter()
+                .map(|(ip_address, grade)| format!("{} ({})", grade, ip_address))
+                .collect();
+            findings.push(Finding {
+                code: "inconsistent_grades".to_string(),
+                exit_code: 1,
+                message: tr("inconsistent_grades", &[&grades.join(", ")]),
+            });
+        }
+        findings
+    }
+
+    /// READY only means the report is complete, cached ones can be days old.
+    pub fn check_report_age(&self, test_time: Option<i64>) -> Vec<Finding> {
+        let (Some(max_age), Some(test_time)) = (self.max_report_age, test_time) else {
+            return vec![];
+        };
+        let age = (Utc::now().timestamp_millis() - test_time) / 3_600_000;
+        if age < max_age as i64 || self.ignores("stale_report") {
+            return vec![];
+        }
+        vec![Finding {
+            code: "stale_report".to_string(),
+            exit_code: 1,
+            message: tr("stale_report", &[&age, &max_age]),
+        }]
+    }
+
+    /// Cached reports save an assessment but may predate the last changes.
+    pub fn check_stale_cache(&self, test_time: Option<i64>) -> Vec<Finding> {
+        let (Some(max_age), Some(test_time)) = (self.warn_stale, test_time) else {
+            return vec![];
+        };
+        let age = (Utc::now().timestamp_millis() - test_time) / 3_600_000;
+        if age < max_age as i64 || self.ignores("stale_cache") {
+            return vec![];
+        }
+        vec![Finding {
+            code: "stale_cache".to_string(),
+            exit_code: 1,
+            message: tr("stale_cache", &[&age, &max_age]),
+        }]
+    }
+
+    /// Only full-detail reports tell when the certificate expires.
+    pub fn check_expiry(&self, not_after: Option<i64>) -> Vec<Finding> {
+        let Some(days) = not_after.map(days_remaining) else {
+            return vec![];
+        };
+        let exit_code = match (self.expiry_critical, self.expiry_warning) {
+            (Some(critical), _) if days < critical => 2,
+            (_, Some(warning)) if days < warning => 1,
+            _ => return vec![],
+        };
+        if self.ignores("expiry") {
+            return vec![];
+        }
+        vec![Finding {
+            code: "expiry".to_string(),
+            exit_code,
+            message: tr("cert_expires", &[&days]),
+        }]
+    }
+
+    /// A wildcard key leaked from any host serves all of its siblings.
+    pub fn check_wildcard(&self, wildcards: &[&str]) -> Vec<Finding> {
+        if !self.deny_wildcard || wildcards.is_empty() || self.ignores("wildcard") {
+            return vec![];
+        }
+        vec![Finding {
+            code: "wildcard".to_string(),
+            exit_code: 2,
+            message: tr("wildcard_denied", &[&wildcards.join(", ")]),
+        }]
+    }
+
+    /// Chrome and Safari reject certificates without SCTs, wherever the
+    /// server delivers them from.
+    pub fn check_sct(&self, sct: Option<Sct>) -> Vec<Finding> {
+        match sct {
+            Some(Sct(0)) if self.require_sct && !self.ignores("sct") => vec![Finding {
+                code: "sct".to_string(),
+                exit_code: 2,
+                message: tr("missing_sct", &[]),
+            }],
+            _ => vec![],
+        }
+    }
+
+    /// Without CAA records any CA may issue a certificate for the domain,
+    /// `iodef` records alone don't restrict anything.
+    pub fn check_caa(&self, caa: Option<&CaaPolicy>) -> Vec<Finding> {
+        let Some(caa) = caa else {
+            return vec![];
+        };
+        let restricted = caa
+            .caa_records
+            .iter()
+            .any(|record| record.restricts_issuance());
+        if !self.require_caa || restricted || self.ignores("caa") {
+            return vec![];
+        }
+        vec![Finding {
+            code: "caa".to_string(),
+            exit_code: 1,
+            message: tr("missing_caa", &[]),
+        }]
+    }
+
+    /// Resumption saves clients a full handshake, while tickets encrypted
+    /// with a key that is never rotated defeat forward secrecy.
+    pub fn check_sessions(&self, resumption: Option<u8>, tickets: Option<u8>) -> Vec<Finding> {
+        let mut findings = vec![];
+        match resumption {
+            Some(resumption)
+                if self.require_resumption && resumption != 2 && !self.ignores("resumption") =>
+            {
+                findings.push(Finding {
+                    code: "resumption".to_string(),
+                    exit_code: 1,
+                    message: tr("no_resumption", &[]),
+                })
+            }
+            _ => (),
+        }
+        match tickets {
+            Some(tickets)
+                if self.deny_session_tickets
+                    && tickets & 1 != 0
+                    && !self.ignores("session_tickets") =>
+            {
+                findings.push(Finding {
+                    code: "session_tickets".to_string(),
+                    exit_code: 1,
+                    message: tr("tickets_denied", &[]),
+                })
+            }
+            _ => (),
+        }
+        findings
+    }
+
+    /// Insecure client-initiated renegotiation lets an attacker inject data
+    /// in the connection, servers lacking secure renegotiation get capped
+    /// grades that thresholds below them wouldn't notice.
+    pub fn check_renegotiation(&self, reneg_support: Option<u8>) -> Vec<Finding> {
+        let Some(reneg_support) = reneg_support.filter(|_| self.check_renegotiation) else {
+            return vec![];
+        };
+        let mut findings = vec![];
+        if reneg_support & 1 != 0 && !self.ignores("insecure_renegotiation") {
+            findings.push(Finding {
+                code: "insecure_renegotiation".to_string(),
+                exit_code: 2,
+                message: tr("insecure_renegotiation", &[]),
+            });
+        }
+        if reneg_support & 2 == 0 && !self.ignores("secure_renegotiation") {
+            findings.push(Finding {
+                code: "secure_renegotiation".to_string(),
+                exit_code: 1,
+                message: tr("no_secure_renegotiation", &[]),
+            });
+        }
+        findings
+    }
+
+    /// Compressed TLS records leak secrets to the CRIME attack.
+    pub fn check_compression(&self, compression_methods: Option<u8>) -> Vec<Finding> {
+        match compression_methods {
+            Some(methods)
+                if self.deny_compression && methods & 1 != 0 && !self.ignores("compression") =>
+            {
+                vec![Finding {
+                    code: "compression".to_string(),
+                    exit_code: 2,
+                    message: tr("compression_enabled", &[]),
+                }]
+            }
+            _ => vec![],
+        }
+    }
+
+    /// A downgrade needs two protocols to pick from, SSL 2 aside, as SSL
+    /// Labs itself only tests it then. One of them is older than TLS 1.3.
+    pub fn check_fallback_scsv(
+        &self,
+        protocols: &[String],
+        fallback_scsv: Option<bool>,
+    ) -> Vec<Finding> {
+        let downgradable: Vec<&String> = protocols
+            .iter()
+            .filter(|protocol| *protocol != "SSL 2.0")
+            .collect();
+        if !self.require_fallback_scsv
+            || fallback_scsv != Some(false)
+            || downgradable.len() < 2
+            || self.ignores("fallback_scsv")
+        {
+            return vec![];
+        }
+        vec![Finding {
+            code: "fallback_scsv".to_string(),
+            exit_code: 1,
+            message: tr("no_fallback_scsv", &[]),
+        }]
+    }
+
+    /// Deprecated protocols can stay enabled behind a good grade, the API
+    /// only caps it for the broken ones. SSL protocols are CRITICAL, TLS
+    /// ones WARNING unless strict.
+    pub fn check_forbidden_protocols(&self, protocols: &[String]) -> Vec<Finding> {
+        let offered: Vec<&ProtocolVersion> = self
+            .forbid_protocols
+            .iter()
+            .filter(|forbidden| protocols.iter().any(|p| p == forbidden.api_name()))
+            .collect();
+        if offered.is_empty() || self.ignores("forbidden_protocol") {
+            return vec![];
+        }
+        let ssl = offered
+            .iter()
+            .any(|version| matches!(version, ProtocolVersion::Ssl2 | ProtocolVersion::Ssl3));
+        let names: Vec<&str> = offered.iter().map(|version| version.api_name()).collect();
+        vec![Finding {
+            code: "forbidden_protocol".to_string(),
+            exit_code: if ssl || self.strict { 2 } else { 1 },
+            message: tr("forbidden_protocols", &[&names.join(", ")]),
+        }]
+    }
+
+    /// Early data can be replayed by an attacker, which APIs that are not
+    /// idempotent can't afford. Only TLS 1.3 endpoints get the test.
+    pub fn check_0rtt(&self, zero_rtt: Option<bool>) -> Vec<Finding> {
+        if !self.deny_0rtt || zero_rtt != Some(true) || self.ignores("0rtt") {
+            return vec![];
+        }
+        vec![Finding {
+            code: "0rtt".to_string(),
+            exit_code: 2,
+            message: tr("early_data_enabled", &[]),
+        }]
+    }
+
+    /// Curves such as secp192r1 no longer offer the strength of the rest of
+    /// the configuration, X25519 is the fastest and safest curve clients
+    /// prefer nowadays.
+    pub fn check_curves(&self, groups: &[NamedGroup]) -> Vec<Finding> {
+        if self.curve_policy == CurvePolicy::Any || groups.is_empty() {
+            return vec![];
+        }
+        let mut findings = vec![];
+        let weak: Vec<&str> = groups
+            .iter()
+            .filter(|group| group.bits.is_some_and(|bits| bits < 256))
+            .map(|group| group.name.as_str())
+            .collect();
+        if !weak.is_empty() && !self.ignores("weak_curves") {
+            findings.push(Finding {
+                code: "weak_curves".to_string(),
+                exit_code: 1,
+                message: tr("weak_curves", &[&weak.join(", ")]),
+            });
+        }
+        let x25519 = groups
+            .iter()
+            .any(|group| group.name.eq_ignore_ascii_case("x25519"));
+        if self.curve_policy == CurvePolicy::Modern && !x25519 && !self.ignores("x25519") {
+            findings.push(Finding {
+                code: "x25519".to_string(),
+                exit_code: 1,
+                message: tr("no_x25519", &[]),
+            });
+        }
+        findings
+    }
+
+    /// Client order lets old clients pick the weakest suite both sides
+    /// support, the setting is easily lost when load balancers are replaced.
+    pub fn check_server_preference(&self, preference: Option<bool>) -> Vec<Finding> {
+        if !self.require_server_preference
+            || preference != Some(false)
+            || self.ignores("server_preference")
+        {
+            return vec![];
+        }
+        vec![Finding {
+            code: "server_preference".to_string(),
+            exit_code: 1,
+            message: tr("no_server_preference", &[]),
+        }]
+    }
+
+    /// Static RSA key exchanges let whoever gets the private key decrypt
+    /// every recorded session, whatever else the forward secrecy summary says.
+    /// Export and RSA_PSK suites transport the key the same way.
+    pub fn check_static_rsa(&self, suites: &[Suite]) -> Vec<Finding> {
+        let static_rsa: Vec<&str> = suites
+            .iter()
+            .map(|suite| suite.name.as_str())
+            .filter(|name| name.starts_with("TLS_RSA_") || name.starts_with("SSL_RSA_"))
+            .collect();
+        if !self.deny_static_rsa || static_rsa.is_empty() || self.ignores("static_rsa") {
+            return vec![];
+        }
+        vec![Finding {
+            code: "static_rsa".to_string(),
+            exit_code: 1,
+            message: tr("static_rsa", &[&static_rsa.join(", ")]),
+        }]
+    }
+
+    /// Suites without forward secrecy let whoever gets the server key decrypt
+    /// recorded traffic, the grade only caps at B when no client negotiates it.
+    pub fn check_forward_secrecy(
+        &self,
+        forward_secrecy: Option<u8>,
+        suites: &[Suite],
+    ) -> Vec<Finding> {
+        if !self.require_forward_secrecy || self.ignores("forward_secrecy") {
+            return vec![];
+        }
+        let without: Vec<&str> = suites
+            .iter()
+            .filter(|suite| !suite.forward_secret())
+            .map(|suite| suite.name.as_str())
+            .collect();
+        let message = match forward_secrecy {
+            _ if !without.is_empty() => tr("no_forward_secrecy", &[&without.join(", ")]),
+            // Bit 2 when every simulated client negotiates it
+            Some(bits) if bits & 4 == 0 => tr("partial_forward_secrecy", &[]),
+            _ => return vec![],
+        };
+        vec![Finding {
+            code: "forward_secrecy".to_string(),
+            exit_code: if self.strict { 2 } else { 1 },
+            message,
+        }]
+    }
+
+    /// The weakest of the certificate key and the key exchanges, compared
+    /// as RSA key sizes so that EC keys and curves weigh alike.
+    pub fn check_key_strength(&self, cert_key: Option<u32>, suites: &[Suite]) -> Vec<Finding> {
+        let Some(min) = self.min_key_strength else {
+            return vec![];
+        };
+        if self.ignores("weak_key") {
+            return vec![];
+        }
+        let exit_code = if self.strict { 2 } else { 1 };
+        let mut findings = vec![];
+        if let Some(bits) = cert_key.filter(|bits| *bits < min) {
+            findings.push(Finding {
+                code: "weak_key".to_string(),
+                exit_code,
+                message: tr("weak_cert_key", &[&bits, &min]),
+            });
+        }
+        let weakest = suites.iter().filter_map(|suite| suite.kx_strength).min();
+        if let Some(bits) = weakest.filter(|bits| *bits < min) {
+            findings.push(Finding {
+                code: "weak_key".to_string(),
+                exit_code,
+                message: tr("weak_key_exchange", &[&bits, &min]),
+            });
+        }
+        findings
+    }
+
+    /// A revoked certificate is CRITICAL whenever the report tells.
+    pub fn check_revocation(&self, revocation_status: Option<u8>) -> Vec<Finding> {
+        if revocation_status != Some(1) || self.ignores("revoked") {
+            return vec![];
+        }
+        vec![Finding {
+            code: "revoked".to_string(),
+            exit_code: 2,
+            message: tr("cert_revoked", &[]),
+        }]
+    }
+
+    /// Trust of the chosen root store, none without `--trust-store` or when
+    /// the report doesn't tell.
+    fn store_trust<'a>(&self, stores: &'a [StoreTrust]) -> Option<&'a StoreTrust> {
+        let store = self.trust_store?;
+        stores.iter().find(|trust| store.is(&trust.store))
+    }
+
+    /// CRITICAL when the chosen root store distrusts a chain graded as
+    /// trusted.
+    pub fn check_trust_store(&self, grade: &Grade, stores: &[StoreTrust]) -> Vec<Finding> {
+        let Some(trust) = self.store_trust(stores) else {
+            return vec![];
+        };
+        if trust.trusted || *grade == Grade::T || self.ignores("trust") {
+            return vec![];
+        }
+        let error = trust.error.as_deref().unwrap_or_default();
+        vec![Finding {
+            code: "trust".to_string(),
+            exit_code: self.if_trust_issues.map_or(2, |state| state.exit_code()),
+            message: tr("not_trusted_by", &[&trust.store, &error]),
+        }]
+    }
+
+    /// Browsers fetch missing intermediates and reorder chains, so a broken
+    /// chain only fails the clients that don't, e.g. APIs and mobile apps.
+    pub fn check_chain(&self, issues: Option<ChainIssues>) -> Vec<Finding> {
+        let Some(issues) = issues else {
+            return vec![];
+        };
+        let descriptions = issues.descriptions();
+        if !self.check_chain || descriptions.is_empty() || self.ignores("chain") {
+            return vec![];
+        }
+        vec![Finding {
+            code: "chain".to_string(),
+            exit_code: 1,
+            message: tr("chain_problems", &[&descriptions.join(", ")]),
+        }]
+    }
+
+    /// Without stapling, clients either query the CA on every connection,
+    /// leaking who visits the site, or don't check revocation at all.
+    pub fn check_ocsp_stapling(&self, ocsp_stapling: Option<bool>) -> Vec<Finding> {
+        if !self.require_ocsp_stapling
+            || ocsp_stapling != Some(false)
+            || self.ignores("ocsp_stapling")
+        {
+            return vec![];
+        }
+        vec![Finding {
+            code: "ocsp_stapling".to_string(),
+            exit_code: 1,
+            message: tr("no_ocsp_stapling", &[]),
+        }]
+    }
+
+    /// Compliance rules often require HSTS with a year of max-age, which
+    /// only weighs on the grade between A and A+. Untested policies pass.
+    pub fn check_hsts(&self, hsts: Option<&str>, max_age: Option<i64>) -> Vec<Finding> {
+        let (Some(minimum), Some(hsts)) = (self.require_hsts, hsts) else {
+            return vec![];
+        };
+        if hsts == "unknown" || self.ignores("hsts") {
+            return vec![];
+        }
+        let message = match max_age {
+            _ if hsts != "present" => tr("hsts_missing", &[&hsts]),
+            Some(max_age) if max_age < minimum as i64 => tr("hsts_short", &[&max_age, &minimum]),
+            _ => return vec![],
+        };
+        vec![Finding {
+            code: "hsts".to_string(),
+            exit_code: 1,
+            message,
+        }]
+    }
+
+    /// Suites whose name contains any of the forbidden parts, regardless of case.
+    pub fn forbidden_suites<'a>(&self, suites: &'a [Suite]) -> Vec<&'a str> {
+        suites
+            .iter()
+            .map(|suite| suite.name.as_str())
+            .filter(|name| {
+                let name = name.to_uppercase();
+                self.forbid_ciphers
+                    .iter()
+                    .any(|part| name.contains(&part.to_uppercase()))
+            })
+            .collect()
+    }
+
+    /// Site-specific cipher requirements go beyond what the grade accounts
+    /// for. The offending suites are listed in the long output.
+    pub fn check_forbidden_ciphers(&self, suites: &[Suite]) -> Vec<Finding> {
+        let forbidden = self.forbidden_suites(suites);
+        if forbidden.is_empty() || self.ignores("forbidden_cipher") {
+            return vec![];
+        }
+        vec![Finding {
+            code: "forbidden_cipher".to_string(),
+            exit_code: if self.strict { 2 } else { 1 },
+            message: tr("forbidden_ciphers", &[&forbidden.len()]),
+        }]
+    }
+
+    /// Ciphers with 64-bit blocks fall to the Sweet32 birthday attack on
+    /// long connections, some criteria versions don't cap the grade for it.
+    pub fn check_sweet32(&self, suites: &[Suite]) -> Vec<Finding> {
+        let weak: Vec<&str> = suites
+            .iter()
+            .map(|suite| suite.name.as_str())
+            .filter(|name| {
+                ["_3DES_", "_DES_", "_DES40_", "_IDEA_", "_RC2_"]
+                    .iter()
+                    .any(|cipher| name.contains(cipher))
+            })
+            .collect();
+        if weak.is_empty() || self.ignores("sweet32") {
+            return vec![];
+        }
+        vec![Finding {
+            code: "sweet32".to_string(),
+            exit_code: if self.strict { 2 } else { 1 },
+            message: tr("sweet32", &[&weak.join(", ")]),
+        }]
+    }
+
+    /// Export suites and weak DH parameters let an attacker downgrade the
+    /// key exchange to something factored in hours (FREAK and Logjam).
+    pub fn check_export(
+        &self,
+        suites: &[Suite],
+        freak: Option<bool>,
+        logjam: Option<bool>,
+    ) -> Vec<Finding> {
+        let mut findings = vec![];
+        let export = suites.iter().any(|suite| suite.name.contains("_EXPORT"));
+        if (export || freak == Some(true)) && !self.ignores("freak") {
+            findings.push(Finding {
+                code: "freak".to_string(),
+                exit_code: 2,
+                message: tr("export_suites", &[]),
+            });
+        }
+        if logjam == Some(true) && !self.ignores("logjam") {
+            findings.push(Finding {
+                code: "logjam".to_string(),
+                exit_code: 2,
+                message: tr("logjam", &[]),
+            });
+        }
+        let weakest = suites.iter().filter_map(|suite| suite.dh_p).min();
+        match weakest {
+            Some(bits) if bits < 2048 && !self.ignores("weak_dh") => findings.push(Finding {
+                code: "weak_dh".to_string(),
+                exit_code: if self.strict { 2 } else { 1 },
+                message: tr("weak_dh", &[&bits]),
+            }),
+            _ => (),
+        }
+        findings
+    }
+
+    /// Appliances that never got the 2014 OpenSSL patch let an attacker in
+    /// the middle force weak keys on the connection.
+    pub fn check_openssl_ccs(&self, openssl_ccs: Option<i8>) -> Vec<Finding> {
+        let exit_code = match openssl_ccs {
+            Some(2) => 1,
+            Some(3) => 2,
+            _ => return vec![],
+        };
+        if !self.check_openssl_ccs || self.ignores("openssl_ccs") {
+            return vec![];
+        }
+        vec![Finding {
+            code: "openssl_ccs".to_string(),
+            exit_code,
+            message: tr("openssl_ccs_vulnerable", &[]),
+        }]
+    }
+
+    /// The API caps the grade of most vulnerable servers, but not always
+    /// below the thresholds, so a known vulnerability can pass unnoticed.
+    pub fn check_vulnerabilities(&self, vulnerabilities: &[String]) -> Vec<Finding> {
+        if !self.fail_on_vuln || vulnerabilities.is_empty() || self.ignores("vulnerability") {
+            return vec![];
+        }
+        vec![Finding {
+            code: "vulnerability".to_string(),
+            exit_code: 2,
+            message: tr("vulnerable", &[&vulnerabilities.join(", ")]),
+        }]
+    }
+
+    /// An issuer missing from the expected ones is either a rogue issuance
+    /// or a CA switch nobody planned. Issuers match on any part of their
+    /// distinguished name, regardless of case.
+    pub fn check_issuer(&self, issuer: Option<&str>) -> Vec<Finding> {
+        let Some(issuer) = issuer else {
+            return vec![];
+        };
+        if self.expect_issuer.is_empty() || self.ignores("issuer") {
+            return vec![];
+        }
+        let lowercase = issuer.to_lowercase();
+        if self
+            .expect_issuer
+            .iter()
+            .any(|expected| lowercase.contains(&expected.to_lowercase()))
+        {
+            return vec![];
+        }
+        vec![Finding {
+            code: "issuer".to_string(),
+            exit_code: 2,
+            message: tr("unexpected_issuer", &[&issuer]),
+        }]
+    }
+
+    /// A certificate replaced long before it was due to expire is either an
+    /// unplanned renewal or someone else's certificate.
+    pub fn check_cert_change(&self, last: &Status, current: &Status, window: i64) -> Vec<Finding> {
+        let (Some(last_fingerprint), Some(fingerprint)) =
+            (&last.cert_fingerprint, &current.cert_fingerprint)
+        else {
+            return vec![];
+        };
+        if last_fingerprint == fingerprint || self.ignores("cert_change") {
+            return vec![];
+        }
+        let days_left = last.cert_not_after.map_or(0, days_remaining);
+        if days_left <= window {
+            return vec![];
+        }
+        vec![Finding {
+            code: "cert_change".to_string(),
+            exit_code: 1,
+            message: tr("cert_changed", &[&days_left]),
+        }]
+    }
+}
+
+/// Whole days left until an expiry given in milliseconds since the epoch.
+pub fn days_remaining(not_after: i64) -> i64 {
+    (not_after - Utc::now().timestamp_millis()).div_euclid(86_400_000)
+}
+
+/// The grade finding of an untrusted or mismatched certificate along with
+/// what the report tells of the cause.
+fn explain(finding: Finding, status: &Status) -> Finding {
+    let message = match finding.code.as_str() {
+        "trust" => {
+            let mut causes = status
+                .cert_issues
+                .map(|issues| issues.trust_descriptions())
+                .unwrap_or_default();
+            // The grade follows Mozilla's root store
+            if causes.is_empty() {
+                causes.extend(
+                    status
+                        .trust_stores
+                        .iter()
+                        .find(|trust| TrustStore::Mozilla.is(&trust.store))
+                        .and_then(|trust| trust.error.clone()),
+                );
+            }
+            if causes.is_empty() {
+                return finding;
+            }
+            tr("not_trusted_because", &[&causes.join(", ")])
+        }
+        "mismatch" => {
+            let mut names: Vec<&str> = status
+                .cert_common_names
+                .iter()
+                .chain(&status.cert_alt_names)
+                .map(String::as_str)
+                .collect();
+            names.dedup();
+            if names.is_empty() {
+                return finding;
+            }
+            tr("name_mismatch_names", &[&names.join(", ")])
+        }
+        _ => return finding,
+    };
+    Finding { message, ..finding }
+}
+
+/// The worst exit code among the findings.
+pub fn exit_code(findings: &[Finding]) -> i32 {
+    findings.iter().map(|f| f.exit_code).max().unwrap_or(0)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// A policy with every check off, warning below A and critical below A-.
+    pub fn policy() -> Policy {
+        Policy {
+            warning: Grade::A,
+            critical: Grade::AMinus,
+            grade_map: None,
+            ignore: Vec::new(),
+            endpoint_policy: EndpointPolicy::default(),
+            address_family: None,
+            expect_endpoints: None,
+            require_consistent_grades: false,
+            max_report_age: None,
+            warn_stale: None,
+            expiry_warning: None,
+            expiry_critical: None,
+            expect_issuer: Vec::new(),
+            deny_wildcard: false,
+            require_sct: false,
+            require_caa: false,
+            require_resumption: false,
+            deny_session_tickets: false,
+            check_renegotiation: false,
+            deny_compression: false,
+            require_fallback_scsv: false,
+            deny_0rtt: false,
+            forbid_protocols: Vec::new(),
+            curve_policy: CurvePolicy::default(),
+            require_server_preference: false,
+            deny_static_rsa: false,
+            require_forward_secrecy: false,
+            min_key_strength: None,
+            check_chain: false,
+            trust_store: None,
+            if_trust_issues: None,
+            require_ocsp_stapling: false,
+            require_hsts: None,
+            forbid_ciphers: Vec::new(),
+            strict: false,
+            check_openssl_ccs: false,
+            fail_on_vuln: false,
+            endpoint_overrides: Vec::new(),
+        }
+    }
+
+    fn exit_codes(findings: &[Finding]) -> Vec<i32> {
+        findings.iter().map(|finding| finding.exit_code).collect()
+    }
+
+    #[test]
+    fn forbidden_protocols() {
+        let cases: &[(&[&str], bool, &[i32])] = &[
+            (&["TLS 1.2", "TLS 1.3"], false, &[]),
+            (&["TLS 1.0", "TLS 1.2"], false, &[1]),
+            (&["TLS 1.0", "TLS 1.2"], true, &[2]),
+            (&["SSL 3.0", "TLS 1.0"], false, &[2]),
+        ];
+        for (protocols, strict, expected) in cases {
+            let policy = Policy {
+                forbid_protocols: vec![ProtocolVersion::Ssl3, ProtocolVersion::Tls1_0],
+                strict: *strict,
+                ..policy()
+            };
+            let protocols: Vec<String> = protocols.iter().map(|p| p.to_string()).collect();
+            let findings = policy.check_forbidden_protocols(&protocols);
+            assert_eq!(exit_codes(&findings), *expected, "{:?}", protocols);
+        }
+    }
+
+    #[test]
+    fn static_rsa() {
+        let cases: &[(&str, bool)] = &[
+            ("TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256", false),
+            ("TLS_DHE_RSA_WITH_AES_256_CBC_SHA", false),
+            ("TLS_AES_128_GCM_SHA256", false),
+            ("TLS_RSA_WITH_AES_128_CBC_SHA", true),
+            ("TLS_RSA_EXPORT_WITH_RC4_40_MD5", true),
+            ("TLS_RSA_EXPORT1024_WITH_DES_CBC_SHA", true),
+            ("TLS_RSA_PSK_WITH_AES_128_CBC_SHA", true),
+            ("SSL_RSA_WITH_RC4_128_MD5", true),
+        ];
+        let policy = Policy {
+            deny_static_rsa: true,
+            ..policy()
+        };
+        for (name, denied) in cases {
+            let suite: Suite =
+                serde_json::from_value(json!({"name": name, "cipherStrength": 128})).unwrap();
+            let findings = policy.check_static_rsa(&[suite]);
+            assert_eq!(!findings.is_empty(), *denied, "{}", name);
+        }
+    }
+
+    #[test]
+    fn expiry_thresholds() {
+        let day = 86_400_000;
+        let cases: &[(i64, &[&str], &[i32])] = &[
+            (40, &[], &[]),
+            (20, &[], &[1]),
+            (3, &[], &[2]),
+            (-1, &[], &[2]),
+            (3, &["expiry"], &[]),
+        ];
+        for (days, ignore, expected) in cases {
+            let policy = Policy {
+                expiry_warning: Some(30),
+                expiry_critical: Some(7),
+                ignore: ignore.iter().map(|code| code.to_string()).collect(),
+                ..policy()
+            };
+            // Half a day on top keeps the count of days whole
+            let not_after = Utc::now().timestamp_millis() + days * day + day / 2;
+            let findings = policy.check_expiry(Some(not_after));
+            assert_eq!(exit_codes(&findings), *expected, "{} days", days);
+        }
+        assert!(policy().check_expiry(Some(0)).is_empty());
+    }
+
+    #[test]
+    fn grade_map() {
+        let map: GradeMap = "A+=0, A=0, A-=1, B=1, *=2".parse().unwrap();
+        assert_eq!(map.to_string(), "A+=0,A=0,A-=1,B=1,*=2");
+        let cases: &[(Grade, &[i32])] = &[
+            (Grade::APlus, &[]),
+            (Grade::AMinus, &[1]),
+            (Grade::C, &[2]),
+            (Grade::Other("Z".to_string()), &[2]),
+        ];
+        let policy = Policy {
+            grade_map: Some(map),
+            ..policy()
+        };
+        for (grade, expected) in cases {
+            let findings = policy.check_grade(grade, None);
+            assert_eq!(exit_codes(&findings), *expected, "{:?}", grade);
+        }
+        for invalid in ["A+=0,A=1", "A=4,*=0", "Z=1,*=0", "A"] {
+            assert!(invalid.parse::<GradeMap>().is_err(), "{}", invalid);
+        }
+    }
+}
@@ -0,0 +1,126 @@
+//! Machine-readable cause of a state that isn't OK.
+//!
+//! Reason codes are coarser than finding codes: alert routing rules branch
+//! on them, while the finding codes tell exactly which check failed.
+
+use crate::Status;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use strum_macros::Display;
+
+#[derive(Clone, Copy, Serialize, Deserialize, JsonSchema, Display, Debug, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+pub enum Reason {
+    /// The grade is below the warning or critical threshold
+    GradeBelowThreshold,
+    /// The certificate isn't trusted (grade T)
+    CertNotTrusted,
+    /// The certificate doesn't match the domain name (grade M)
+    CertNameMismatch,
+    /// The certificate expires within the thresholds
+    CertExpiring,
+    /// The certificate changed outside of its renewal window
+    CertChanged,
+    /// The certificate doesn't meet the certificate policies (issuer, wildcard, SCT, chain, stapling)
+    CertPolicy,
+    /// Protocols, cipher suites or session handling don't meet the policies
+    TlsConfiguration,
+    /// The server is vulnerable to a known attack
+    Vulnerability,
+    /// The endpoints reported don't meet the expectations
+    Endpoints,
+    /// The report is older than allowed
+    StaleReport,
+    /// The API refused the request under its rate limits
+    ApiRateLimited,
+    /// The API couldn't be reached or is overloaded
+    ApiUnavailable,
+    /// The API replied with something that couldn't be made sense of
+    ApiError,
+    /// The API couldn't resolve the domain name
+    DnsFailure,
+    /// The API ended the assessment in error
+    AssessmentFailed,
+    /// The assessment wasn't over before the attemps ran out
+    AssessmentTimeout,
+    /// The API reported no endpoint for the domain
+    NoEndpoint,
+    /// The domain name is invalid
+    InvalidDomain,
+    /// The check didn't complete within --timeout
+    PluginTimeout,
+    /// The plugin failed on its own, e.g. reading its inventory or writing its state
+    PluginError,
+}
+
+impl Reason {
+    /// Reason of a finding, from its code.
+    pub fn of_finding(code: &str) -> Reason {
+        match code {
+            "grade" => Reason::GradeBelowThreshold,
+            "trust" | "revoked" => Reason::CertNotTrusted,
+            "mismatch" => Reason::CertNameMismatch,
+            "expiry" => Reason::CertExpiring,
+            "cert_change" => Reason::CertChanged,
+            "issuer" | "wildcard" | "sct" | "caa" | "ocsp_stapling" | "chain" => Reason::CertPolicy,
+            "freak" | "logjam" | "openssl_ccs" | "sweet32" | "vulnerability" => {
+                Reason::Vulnerability
+            }
+            "endpoints" | "inconsistent_grades" => Reason::Endpoints,
+            "stale_report" | "stale_cache" => Reason::StaleReport,
+            "unknown_grade" => Reason::ApiError,
+            _ => Reason::TlsConfiguration,
+        }
+    }
+
+    /// Reason of a failed API call, that of the first `ReasonError` among
+    /// the error and its sources when there is one.
+    pub fn of_error(error: &(dyn Error + 'static)) -> Reason {
+        let mut source = Some(error);
+        while let Some(error) = source {
+            if let Some(error) = error.downcast_ref::<ReasonError>() {
+                return error.reason;
+            }
+            source = error.source();
+        }
+        match error.downcast_ref::<reqwest::Error>() {
+            Some(e) if e.is_timeout() || e.is_connect() => Reason::ApiUnavailable,
+            _ => Reason::ApiError,
+        }
+    }
+}
+
+/// An error whose reason is known where it occurs.
+#[derive(Debug)]
+pub struct ReasonError {
+    pub reason: Reason,
+    pub message: String,
+}
+
+impl fmt::Display for ReasonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl Error for ReasonError {}
+
+/// Reason of the state of a status, none when it is OK. Among findings,
+/// the first of the worst ones gives it.
+pub fn of_status(status: &Status) -> Option<Reason> {
+    if status.exit_code == 0 {
+        return None;
+    }
+    if status.error_reason.is_some() {
+        return status.error_reason;
+    }
+    status
+        .findings
+        .iter()
+        .filter(|finding| finding.exit_code == status.exit_code)
+        .map(|finding| Reason::of_finding(&finding.code))
+        .next()
+}
@@ -0,0 +1,88 @@
+use crate::i18n::tr;
+use crate::{api_client, runtime, Cli, API_V3, API_V4};
+use clap::Args;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+
+#[derive(Args, Debug)]
+pub struct RegisterEmailArgs {
+    #[arg(long)]
+    pub first_name: String,
+
+    #[arg(long)]
+    pub last_name: String,
+
+    #[arg(long)]
+    pub organization: String,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Registration<'a> {
+    first_name: &'a str,
+    last_name: &'a str,
+    email: &'a str,
+    organization: &'a str,
+}
+
+/// Reply of the `register` call, `errors` only when it is refused.
+#[derive(Deserialize, Debug)]
+struct Reply {
+    message: Option<String>,
+    #[serde(default)]
+    errors: Vec<Field>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Field {
+    field: Option<String>,
+    message: String,
+}
+
+/// Register the `--email` address with API v4, once and for all. SSL Labs
+/// only accepts organization addresses, not those of free providers.
+pub fn register_email(args: &RegisterEmailArgs, cli: &Cli) -> Result<(), Box<dyn Error>> {
+    let email = cli
+        .email
+        .as_deref()
+        .ok_or_else(|| tr("email_required", &[]))?;
+    let registration = Registration {
+        first_name: &args.first_name,
+        last_name: &args.last_name,
+        email,
+        organization: &args.organization,
+    };
+    // Only API v4 registers, whatever the version the checks use
+    let base = match cli.api_url.as_str() {
+        API_V3 => API_V4.to_string(),
+        _ => cli.api_base(),
+    };
+    // Through the proxy, CAs, identity and headers of the checks
+    let client = api_client(cli)?;
+    let (status, reply) = runtime()?.block_on(async {
+        let response = client
+            .post(format!("{}register", base))
+            .json(&registration)
+            .send()
+            .await?;
+        let status = response.status();
+        let reply: Reply = response.json().await?;
+        Ok::<_, reqwest::Error>((status, reply))
+    })?;
+    if !status.is_success() || !reply.errors.is_empty() {
+        let errors: Vec<String> = reply
+            .errors
+            .iter()
+            .map(|error| match &error.field {
+                Some(field) => format!("{}: {}", field, error.message),
+                None => error.message.clone(),
+            })
+            .collect();
+        return Err(tr("registration_refused", &[&status, &errors.join(", ")]).into());
+    }
+    println!(
+        "{}",
+        reply.message.unwrap_or_else(|| tr("registered", &[&email]))
+    );
+    Ok(())
+}
@@ -0,0 +1,579 @@
+use crate::i18n::tr;
+use crate::reason::{Reason, ReasonError};
+use crate::tape::Tape;
+use crate::{
+    get_api_body, get_endpoint_data, get_info, is_transient, process_response_body, ApiResponse,
+    Cli, Endpoint, Info, OnTimeout, Response, State, Status, Target, Throttled, HTTP_TIMEOUT,
+};
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use reqwest::Client;
+use std::collections::hash_map::RandomState;
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use std::hash::{BuildHasher, Hasher};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use tokio::task;
+use tokio::time;
+
+/// Seconds to pause after `polls` polls, before jitter: `--time` stretched
+/// by `--backoff` after each poll, up to `--max-delay`.
+pub fn base_delay(cli: &Cli, polls: u32) -> f64 {
+    let exponent = i32::try_from(polls).unwrap_or(i32::MAX);
+    let delay = f64::from(cli.time) * cli.backoff.powi(exponent);
+    delay.min(f64::from(cli.max_delay))
+}
+
+/// Longest an assessment can be polled for: every poll of the name
+/// resolution and attemps budgets with the longest jitter, the last of
+/// which may hang until it times out.
+pub fn budget(cli: &Cli) -> Duration {
+    let polls = u32::from(cli.attemps) + u32::from(cli.dns_attempts);
+    let pauses: f64 = (0..polls).map(|polls| base_delay(cli, polls)).sum();
+    Duration::from_secs_f64(pauses * (1.0 + cli.jitter)) + HTTP_TIMEOUT
+}
+
+/// Pause after `polls` polls. Replayed responses are polled through
+/// without waiting.
+fn poll_delay(cli: &Cli, polls: u32) -> Duration {
+    if cli.plays_back() {
+        return Duration::ZERO;
+    }
+    let spread = cli.jitter * (2.0 * random_unit() - 1.0);
+    Duration::from_secs_f64(base_delay(cli, polls) * (1.0 + spread))
+}
+
+/// Uniform in [0, 1), drawn from the random keys std seeds hash maps with.
+fn random_unit() -> f64 {
+    let bits = RandomState::new().build_hasher().finish();
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Concurrency allowed until the API tells otherwise.
+#[derive(Debug)]
+struct Limits {
+    max_assessments: usize,
+    current_assessments: Option<usize>,
+    /// Most assessments of ours, whatever the API allows
+    concurrency: usize,
+    cool_off: Duration,
+    /// No assessment is launched before, as the API asked
+    retry_at: Option<Instant>,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_assessments: 1,
+            current_assessments: None,
+            concurrency: usize::MAX,
+            cool_off: Duration::from_secs(1),
+            retry_at: None,
+        }
+    }
+}
+
+impl Limits {
+    fn update(&mut self, response: &ApiResponse) {
+        if let Some(max) = response.max_assessments {
+            self.max_assessments = max.max(1);
+        }
+        self.current_assessments = response.current_assessments;
+    }
+
+    /// The API also counts assessments started by other clients sharing our
+    /// address, its figure is only trusted while we have assessments of our
+    /// own to refresh it.
+    fn can_launch(&self, active: usize) -> bool {
+        active < self.max_assessments.min(self.concurrency)
+            && (active == 0
+                || self
+                    .current_assessments
+                    .is_none_or(|current| current < self.max_assessments))
+    }
+}
+
+/// An assessment started on the API and polled until it is over.
+struct Assessment<'a> {
+    target: &'a Target,
+    status: Status,
+    attempts: u8,
+    dns_attempts: u8,
+    /// Times the assessment was started anew after the API ended it in ERROR
+    restarts: u8,
+    /// Requests in a row that failed on the way
+    failures: u8,
+    /// Whether the next poll starts a new assessment
+    start_new: bool,
+    /// Polls since the assessment was started, which the pauses grow with
+    polls: u32,
+    /// Whether the API took the assessment on, rather than refusing it
+    /// for want of capacity
+    accepted: bool,
+    /// Endpoints fetched with `--endpoint-data`, by address
+    endpoints: HashMap<String, Endpoint>,
+    started: Instant,
+    next_poll: Instant,
+    bar: ProgressBar,
+}
+
+impl<'a> Assessment<'a> {
+    fn poll(&mut self, cli: &Cli, body: Response) -> bool {
+        let state = State::from_str(&body.status);
+        let resolving = state == Ok(State::Dns);
+        self.start_new = false;
+        self.accepted = true;
+        self.failures = 0;
+        if state == Ok(State::Error) && self.restarts < cli.error_retries {
+            self.restarts += 1;
+            if cli.verbose >= 2 {
+                let message = body.status_message.as_deref().unwrap_or_default();
+                self.bar.suspend(|| {
+                    eprintln!(
+                        "{}: assessment failed ({}), starting it anew",
+                        self.target.domain, message
+                    )
+                });
+            }
+            self.start_new = true;
+            self.attempts = 0;
+            self.dns_attempts = 0;
+            self.bar.set_position(0);
+            self.polls = 0;
+            self.schedule(cli);
+            return false;
+        }
+        let progress = progress(&body);
+        let status = std::mem::take(&mut self.status);
+        let phase = status.phase.clone();
+        self.status = match process_response_body(body, status, &self.target.policy) {
+            Ok(st) => st,
+            Err(e) => return self.fail(Reason::of_error(&*e), e.to_string()),
+        };
+        if self.status.phase != phase {
+            if let Some(phase) = &self.status.phase {
+                if cli.verbose >= 2 {
+                    self.bar
+                        .suspend(|| eprintln!("{}: {}", self.target.domain, phase));
+                }
+            }
+        }
+        match &self.status.phase {
+            Some(phase) => self.bar.set_message(format!("{}, {}", progress, phase)),
+            None => self.bar.set_message(progress),
+        }
+        self.schedule(cli);
+        // Resolving names can take a while on the API side, it has a
+        // budget of its own so that slow ones don't eat the attemps.
+        if resolving {
+            self.dns_attempts += 1;
+            if self.dns_attempts > cli.dns_attempts {
+                let error = tr("dns_stuck", &[&self.dns_attempts]);
+                return self.fail(Reason::DnsFailure, error);
+            }
+            return false;
+        }
+        self.attempts += 1;
+        self.bar.inc(1);
+        if !self.status.ready && self.attempts > cli.attemps {
+            self.status.timed_out = true;
+            self.status.exit_code = match cli.on_timeout {
+                OnTimeout::Warning => 1,
+                _ => 3,
+            };
+            self.status.error = Some(tr("attempts_exhausted", &[&cli.attemps]));
+            self.status.error_reason = Some(Reason::AssessmentTimeout);
+            return true;
+        }
+        self.status.ready
+    }
+
+    /// Fetch the endpoints graded since the last poll, then once the
+    /// assessment is over stand them for those reported without details.
+    async fn fetch_endpoints(
+        &mut self,
+        client: &Client,
+        cli: &Cli,
+        tape: &mut Tape,
+        body: &mut Response,
+        timeout: Duration,
+    ) {
+        let ready = body.status == State::Ready.to_string();
+        for endpoint in body.endpoints.iter_mut().flatten() {
+            let Some(ip) = endpoint.ip_address.clone() else {
+                continue;
+            };
+            if endpoint.status_message.as_deref() != Some("Ready") {
+                continue;
+            }
+            if !self.endpoints.contains_key(&ip) {
+                let domain = &self.target.domain;
+                // The analyze call still reports the endpoint otherwise
+                match get_endpoint_data(client, cli, tape, domain, &ip, timeout).await {
+                    Ok(data) => {
+                        let grade = data.grade.clone().unwrap_or_default();
+                        if cli.verbose >= 2 {
+                            self.bar.suspend(|| {
+                                eprintln!("{}: endpoint {} graded {}", domain, ip, grade)
+                            });
+                        }
+                        self.bar.set_message(format!("{} {}", ip, grade));
+                        self.endpoints.insert(ip.clone(), data);
+                    }
+                    Err(e) if cli.verbose >= 2 => self
+                        .bar
+                        .suspend(|| eprintln!("{}: endpoint {} unavailable: {}", domain, ip, e)),
+                    Err(_) => (),
+                }
+            }
+            if ready && endpoint.details.is_none() {
+                if let Some(data) = self.endpoints.remove(&ip) {
+                    *endpoint = data;
+                }
+            }
+        }
+    }
+
+    /// Set the duration of an assessment that is over.
+    fn finish(mut self) -> (&'a Target, Status) {
+        self.status.duration = Some(self.started.elapsed().as_secs());
+        self.bar.finish();
+        (self.target, self.status)
+    }
+
+    /// Poll again after the delay the API asked for, or the usual one, when
+    /// it rate limits or is overloaded. Each such reply counts as one attemp.
+    fn defer(&mut self, cli: &Cli, throttled: &Throttled, retry_after: Option<Duration>) -> bool {
+        self.attempts += 1;
+        self.bar.inc(1);
+        let error = throttled.to_string();
+        if self.attempts > cli.attemps {
+            return self.fail(Reason::of_error(throttled), error);
+        }
+        match retry_after {
+            Some(delay) => self.next_poll = Instant::now() + delay,
+            None => self.schedule(cli),
+        }
+        if cli.verbose >= 2 {
+            let delay = self.next_poll.saturating_duration_since(Instant::now());
+            self.bar.suspend(|| {
+                eprintln!(
+                    "{}: {}, polling again in {}s",
+                    self.target.domain,
+                    error,
+                    delay.as_secs_f64().round()
+                )
+            });
+        }
+        false
+    }
+
+    /// Send the request again after `--retry-delay` when it failed on the
+    /// way, up to `--retries` times in a row.
+    fn retry(&mut self, cli: &Cli, error: &(dyn Error + 'static)) -> bool {
+        if self.failures >= cli.retries {
+            return self.fail(Reason::of_error(error), error.to_string());
+        }
+        self.failures += 1;
+        let delay = match cli.plays_back() {
+            true => Duration::ZERO,
+            false => Duration::from_secs(cli.retry_delay.into()),
+        };
+        self.next_poll = Instant::now() + delay;
+        if cli.verbose >= 2 {
+            self.bar.suspend(|| {
+                eprintln!(
+                    "{}: {}, retrying in {}s ({}/{})",
+                    self.target.domain,
+                    error,
+                    delay.as_secs(),
+                    self.failures,
+                    cli.retries
+                )
+            });
+        }
+        false
+    }
+
+    fn schedule(&mut self, cli: &Cli) {
+        self.next_poll = Instant::now() + poll_delay(cli, self.polls);
+        self.polls += 1;
+    }
+
+    /// Give up on an assessment the API replies can't be made sense of.
+    fn fail(&mut self, reason: Reason, error: String) -> bool {
+        self.status.status = State::Unknown;
+        self.status.exit_code = 3;
+        self.status.error = Some(error);
+        self.status.error_reason = Some(reason);
+        true
+    }
+}
+
+/// Service limits and versions from the `info` call, sent again as any
+/// other request when it fails on the way. An API that can't be reached
+/// ends the run at once unless the local fallback is to take over, other
+/// failures leave the defaults.
+async fn preflight(
+    client: &Client,
+    cli: &Cli,
+    tape: &mut Tape,
+    timeout: impl Fn(Instant) -> Duration,
+) -> Result<Option<Info>, Box<dyn Error>> {
+    let mut failures = 0;
+    let (error, unavailable) = loop {
+        let error = match get_info(client, cli, tape, timeout(Instant::now())).await {
+            Ok(info) => return Ok(Some(info)),
+            Err(e) => e,
+        };
+        let unavailable = is_transient(&*error)
+            || error
+                .downcast_ref::<Throttled>()
+                .is_some_and(|throttled| Reason::of_error(throttled) == Reason::ApiUnavailable);
+        let delay = Duration::from_secs(cli.retry_delay.into());
+        // Retries don't outlast the deadline either
+        if !unavailable
+            || failures >= cli.retries
+            || tape.replaying()
+            || timeout(Instant::now() + delay).is_zero()
+        {
+            break (error, unavailable);
+        }
+        failures += 1;
+        if cli.verbose >= 2 {
+            eprintln!(
+                "API info: {}, retrying in {}s ({}/{})",
+                error,
+                delay.as_secs(),
+                failures,
+                cli.retries
+            );
+        }
+        time::sleep(delay).await;
+    };
+    if unavailable && cli.fallback.is_none() {
+        return Err(Box::new(ReasonError {
+            reason: Reason::ApiUnavailable,
+            message: tr("api_down", &[&error]),
+        }));
+    }
+    if cli.verbose >= 2 {
+        eprintln!("API info unavailable: {}", error);
+    }
+    Ok(None)
+}
+
+/// The API state of an assessment, along the progress and time left of
+/// the endpoint being tested while it is in progress.
+fn progress(body: &Response) -> String {
+    let mut progress = body.status.clone();
+    let testing = body.endpoints.iter().flatten().find(|endpoint| {
+        endpoint
+            .progress
+            .is_some_and(|progress| (0..100).contains(&progress))
+    });
+    if let Some(endpoint) = testing {
+        progress.push_str(&format!(" {}%", endpoint.progress.unwrap_or_default()));
+        if let Some(eta) = endpoint.eta.filter(|eta| *eta > 0) {
+            progress.push_str(&format!(", ETA {}s", eta));
+        }
+    }
+    progress
+}
+
+fn bar_style() -> ProgressStyle {
+    ProgressStyle::with_template("{prefix} {spinner} {elapsed:>4} {msg}")
+        .unwrap_or_else(|_| ProgressStyle::default_spinner())
+}
+
+/// Assess every target, interleaving the polls of running assessments with
+/// the launch of new ones as far as the API concurrency and cool-off limits
+/// allow. `done` is called with each result as soon as it is over, off the
+/// runtime as it blocks on notifications and local scans.
+pub async fn run<'a, F>(
+    client: &Client,
+    cli: &Cli,
+    targets: Vec<&'a Target>,
+    deadline: Option<Instant>,
+    mut done: F,
+) -> Result<(), Box<dyn Error>>
+where
+    F: FnMut(&'a Target, Status) -> Result<(), Box<dyn Error>>,
+{
+    // Requests don't outlast the deadline
+    let timeout = |now: Instant| {
+        deadline.map_or(HTTP_TIMEOUT, |deadline| {
+            deadline.saturating_duration_since(now).min(HTTP_TIMEOUT)
+        })
+    };
+    let mut tape = Tape::new(cli);
+    let mut limits = Limits::default();
+    let info = preflight(client, cli, &mut tape, timeout).await?;
+    if let Some(info) = &info {
+        if let Some(max) = info.max_assessments {
+            limits.max_assessments = max.max(1);
+        }
+        if let Some(cool_off) = info.new_assessment_cool_off {
+            limits.cool_off = Duration::from_millis(cool_off);
+        }
+        if cli.verbose >= 2 {
+            eprintln!(
+                "API engine {}, criteria {}",
+                info.engine_version.as_deref().unwrap_or("unknown"),
+                info.criteria_version.as_deref().unwrap_or("unknown")
+            );
+        }
+    }
+    if let Some(concurrency) = cli.concurrency {
+        limits.concurrency = concurrency.into();
+    }
+    if tape.replaying() {
+        limits.cool_off = Duration::ZERO;
+    }
+    if cli.verbose >= 2 {
+        eprintln!("Scheduling limits: {:?}", limits);
+    }
+
+    let multi = MultiProgress::new();
+    if !cli.progress {
+        multi.set_draw_target(ProgressDrawTarget::hidden());
+    }
+    let mut pending: VecDeque<&Target> = targets.into();
+    let mut active: Vec<Assessment> = Vec::new();
+    let mut last_launch: Option<Instant> = None;
+    while !pending.is_empty() || !active.is_empty() {
+        let now = Instant::now();
+        if deadline.is_some_and(|deadline| now >= deadline) {
+            let error = tr("deadline_exceeded", &[&cli.timeout.unwrap_or_default()]);
+            for mut assessment in active.drain(..) {
+                assessment.fail(Reason::PluginTimeout, error.clone());
+                let (target, status) = assessment.finish();
+                task::block_in_place(|| multi.suspend(|| done(target, status)))?;
+            }
+            for target in pending.drain(..) {
+                let status = Status {
+                    exit_code: 3,
+                    error: Some(error.clone()),
+                    error_reason: Some(Reason::PluginTimeout),
+                    ..Default::default()
+                };
+                task::block_in_place(|| multi.suspend(|| done(target, status)))?;
+            }
+            break;
+        }
+        let next_launch = last_launch
+            .map_or(now, |at| at + limits.cool_off)
+            .max(limits.retry_at.unwrap_or(now));
+        let can_launch = !pending.is_empty() && limits.can_launch(active.len());
+        if can_launch && next_launch <= now {
+            if let Some(target) = pending.pop_front() {
+                let bar = multi.add(
+                    ProgressBar::new(cli.attemps.into())
+                        .with_style(bar_style())
+                        .with_prefix(target.domain.clone()),
+                );
+                if cli.progress {
+                    bar.enable_steady_tick(Duration::from_millis(200));
+                }
+                active.push(Assessment {
+                    target,
+                    status: Status {
+                        engine_version: info.as_ref().and_then(|i| i.engine_version.clone()),
+                        criteria_version: info.as_ref().and_then(|i| i.criteria_version.clone()),
+                        ..Default::default()
+                    },
+                    attempts: 0,
+                    dns_attempts: 0,
+                    restarts: 0,
+                    failures: 0,
+                    start_new: cli.start_new,
+                    polls: 0,
+                    accepted: false,
+                    endpoints: HashMap::new(),
+                    started: now,
+                    next_poll: now,
+                    bar,
+                });
+                last_launch = Some(now);
+            }
+        }
+
+        let due = active
+            .iter()
+            .enumerate()
+            .filter(|(_, assessment)| assessment.next_poll <= now)
+            .min_by_key(|(_, assessment)| assessment.next_poll)
+            .map(|(index, _)| index);
+        match due {
+            Some(index) => {
+                let assessment = &active[index];
+                let target = assessment.target;
+                let domain = &target.domain;
+                let start_new = assessment.start_new;
+                let response =
+                    get_api_body(client, cli, &mut tape, target, start_new, timeout(now)).await;
+                let finished = match response {
+                    Ok(mut response) => {
+                        if cli.verbose >= 2 {
+                            let state = &response.body.status;
+                            multi.suspend(|| eprintln!("{}: {}", domain, state));
+                        }
+                        limits.update(&response);
+                        if cli.endpoint_data {
+                            let timeout = timeout(Instant::now());
+                            active[index]
+                                .fetch_endpoints(
+                                    client,
+                                    cli,
+                                    &mut tape,
+                                    &mut response.body,
+                                    timeout,
+                                )
+                                .await;
+                        }
+                        active[index].poll(cli, response.body)
+                    }
+                    // Cut short by the deadline, which the next round reports
+                    Err(_) if deadline.is_some_and(|deadline| Instant::now() >= deadline) => false,
+                    Err(e) => match e.downcast_ref::<Throttled>() {
+                        Some(throttled) => {
+                            // Replayed responses are polled through without waiting
+                            let retry_after = throttled
+                                .retry_after
+                                .filter(|_| !tape.replaying())
+                                .map(Duration::from_secs);
+                            if let Some(delay) = retry_after {
+                                limits.retry_at = Some(Instant::now() + delay);
+                            }
+                            // Nothing more is launched until one of ours is over
+                            let refused = Reason::of_error(throttled) == Reason::ApiRateLimited;
+                            if refused && !assessment.accepted {
+                                limits.max_assessments = active.len().max(1);
+                            }
+                            active[index].defer(cli, throttled, retry_after)
+                        }
+                        None if is_transient(&*e) => active[index].retry(cli, &*e),
+                        None => active[index].fail(Reason::of_error(&*e), e.to_string()),
+                    },
+                };
+                if finished {
+                    let (target, status) = active.remove(index).finish();
+                    task::block_in_place(|| multi.suspend(|| done(target, status)))?;
+                }
+            }
+            None => {
+                let mut wake = active.iter().map(|assessment| assessment.next_poll).min();
+                if can_launch {
+                    wake = Some(wake.map_or(next_launch, |wake| wake.min(next_launch)));
+                }
+                if let Some(deadline) = deadline {
+                    wake = Some(wake.map_or(deadline, |wake| wake.min(deadline)));
+                }
+                if let Some(wake) = wake {
+                    time::sleep_until(wake.into()).await;
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,135 @@
+use serde_json::{Map, Value};
+use std::error::Error;
+
+/// Fields documented for the objects of the API v3 `analyze` call, see
+/// https://github.com/ssllabs/ssllabs-scan/blob/master/ssllabs-api-docs-v3.md
+const HOST: &[&str] = &[
+    "host",
+    "port",
+    "protocol",
+    "isPublic",
+    "status",
+    "statusMessage",
+    "startTime",
+    "testTime",
+    "engineVersion",
+    "criteriaVersion",
+    "cacheExpiryTime",
+    "certHostnames",
+    "endpoints",
+    "certs",
+];
+
+const ENDPOINT: &[&str] = &[
+    "ipAddress",
+    "serverName",
+    "statusMessage",
+    "statusDetails",
+    "statusDetailsMessage",
+    "grade",
+    "gradeTrustIgnored",
+    "futureGrade",
+    "hasWarnings",
+    "isExceptional",
+    "progress",
+    "duration",
+    "eta",
+    "delegation",
+    "details",
+];
+
+const CERT: &[&str] = &[
+    "id",
+    "subject",
+    "serialNumber",
+    "commonNames",
+    "altNames",
+    "notBefore",
+    "notAfter",
+    "issuerSubject",
+    "sigAlg",
+    "revocationInfo",
+    "crlURIs",
+    "ocspURIs",
+    "revocationStatus",
+    "crlRevocationStatus",
+    "ocspRevocationStatus",
+    "dnsCaa",
+    "caaPolicy",
+    "mustStaple",
+    "sgc",
+    "validationType",
+    "issues",
+    "sct",
+    "sha1Hash",
+    "sha256Hash",
+    "pinSha256",
+    "keyAlg",
+    "keySize",
+    "keyStrength",
+    "keyKnownDebianInsecure",
+    "raw",
+];
+
+/// Check an `analyze` response against the documented schema: no field
+/// unknown to the documentation and none of those the checks rely on
+/// missing. The first reply of a new assessment carries no endpoint yet,
+/// the fields of an endpoint are only all there once it is ready.
+pub fn check(body: &Value) -> Result<(), Box<dyn Error>> {
+    let host = object(body, "host")?;
+    known(host, HOST, "host")?;
+    required(host, &["host", "status"], "host")?;
+    let ready = host.get("status").and_then(Value::as_str) == Some("READY");
+    if ready {
+        required(host, &["endpoints", "testTime"], "host")?;
+    }
+    for endpoint in array(host, "endpoints")? {
+        let endpoint = object(endpoint, "endpoint")?;
+        known(endpoint, ENDPOINT, "endpoint")?;
+        required(endpoint, &["ipAddress", "statusMessage"], "endpoint")?;
+        if endpoint.get("statusMessage").and_then(Value::as_str) == Some("Ready") {
+            required(endpoint, &["grade", "gradeTrustIgnored"], "endpoint")?;
+        }
+    }
+    for cert in array(host, "certs")? {
+        let cert = object(cert, "cert")?;
+        known(cert, CERT, "cert")?;
+        required(cert, &["id", "sha256Hash", "notAfter"], "cert")?;
+    }
+    Ok(())
+}
+
+fn object<'a>(value: &'a Value, name: &str) -> Result<&'a Map<String, Value>, Box<dyn Error>> {
+    value
+        .as_object()
+        .ok_or_else(|| format!("schema: {} is not an object", name).into())
+}
+
+fn array<'a>(object: &'a Map<String, Value>, name: &str) -> Result<&'a [Value], Box<dyn Error>> {
+    match object.get(name) {
+        None | Some(Value::Null) => Ok(&[]),
+        Some(Value::Array(values)) => Ok(values),
+        Some(_) => Err(format!("schema: {} is not an array", name).into()),
+    }
+}
+
+fn known(object: &Map<String, Value>, fields: &[&str], name: &str) -> Result<(), Box<dyn Error>> {
+    match object.keys().find(|key| !fields.contains(&key.as_str())) {
+        Some(key) => Err(format!("schema: unknown field {} in {}", key, name).into()),
+        None => Ok(()),
+    }
+}
+
+fn required(
+    object: &Map<String, Value>,
+    fields: &[&str],
+    name: &str,
+) -> Result<(), Box<dyn Error>> {
+    match fields
+        .iter()
+        .find(|field| object.get(**field).is_none_or(Value::is_null))
+    {
+        Some(field) => Err(format!("schema: missing field {} in {}", field, name).into()),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,102 @@
+//! Numeric category scores along the SSL Labs rating guide, see
+//! https://github.com/ssllabs/research/wiki/SSL-Server-Rating-Guide
+//!
+//! The API only publishes the letter grade, the scores are worked out from
+//! the protocols, suites and keys of the full-detail report. They move when
+//! a configuration change doesn't show in the grade.
+
+use crate::Suite;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Scores out of 100 of the rating categories.
+#[derive(Clone, Copy, Serialize, Deserialize, JsonSchema, Debug, PartialEq)]
+pub struct Scores {
+    /// 100 unless the certificate isn't trusted or doesn't match, which zeroes the grade
+    pub certificate: u8,
+    pub protocol_support: u8,
+    pub key_exchange: u8,
+    pub cipher_strength: u8,
+}
+
+impl Scores {
+    /// Scores of an endpoint, none without the protocols and suites of a
+    /// full-detail report.
+    pub fn new(
+        trusted: bool,
+        protocols: &[String],
+        suites: &[Suite],
+        key_strength: Option<u32>,
+    ) -> Option<Scores> {
+        let protocol_support = best_and_worst(protocols.iter().filter_map(|p| protocol(p)))?;
+        let cipher_strength = best_and_worst(
+            suites
+                .iter()
+                .filter_map(|suite| suite.cipher_strength.map(cipher)),
+        )?;
+        // The weakest of the server key and the DH parameters
+        let key_exchange = key_strength
+            .into_iter()
+            .chain(suites.iter().filter_map(|suite| suite.dh_p.map(u32::from)))
+            .min()
+            .map(key)?;
+        Some(Scores {
+            certificate: if trusted { 100 } else { 0 },
+            protocol_support,
+            key_exchange,
+            cipher_strength,
+        })
+    }
+
+    /// Overall score, 30% protocol support, 30% key exchange and 40% cipher
+    /// strength, zero when the certificate fails.
+    pub fn overall(&self) -> u8 {
+        if self.certificate == 0 {
+            return 0;
+        }
+        let weighted = u32::from(self.protocol_support) * 3
+            + u32::from(self.key_exchange) * 3
+            + u32::from(self.cipher_strength) * 4;
+        (weighted / 10) as u8
+    }
+}
+
+/// Average of the best and the worst scores.
+fn best_and_worst(scores: impl Iterator<Item = u8>) -> Option<u8> {
+    let scores: Vec<u8> = scores.collect();
+    let best = scores.iter().max()?;
+    let worst = scores.iter().min()?;
+    Some(((u16::from(*best) + u16::from(*worst)) / 2) as u8)
+}
+
+fn protocol(protocol: &str) -> Option<u8> {
+    match protocol {
+        "SSL 2.0" => Some(0),
+        "SSL 3.0" => Some(80),
+        "TLS 1.0" => Some(90),
+        "TLS 1.1" => Some(95),
+        "TLS 1.2" | "TLS 1.3" => Some(100),
+        _ => None,
+    }
+}
+
+/// Score of an RSA-equivalent key size in bits.
+fn key(bits: u32) -> u8 {
+    match bits {
+        0 => 0,
+        1..=511 => 20,
+        512..=1023 => 40,
+        1024..=2047 => 80,
+        2048..=4095 => 90,
+        _ => 100,
+    }
+}
+
+fn cipher(bits: u16) -> u8 {
+    match bits {
+        0 => 0,
+        1..=127 => 20,
+        128..=255 => 80,
+        _ => 100,
+    }
+}
@@ -0,0 +1,161 @@
+use crate::i18n::tr;
+use clap::Args;
+use openssl::hash::MessageDigest;
+use openssl::pkey::PKey;
+use openssl::sign::Verifier;
+use reqwest::blocking::Client;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::env;
+use std::error::Error;
+use std::fs;
+
+/// Name of the release asset listing the SHA-256 of every binary
+const CHECKSUMS_ASSET: &str = "SHA256SUMS";
+
+/// Name of the release asset signing `SHA256SUMS`, as made by
+/// `openssl dgst -sha256 -sign release.key -out SHA256SUMS.sig SHA256SUMS`
+const SIGNATURE_ASSET: &str = "SHA256SUMS.sig";
+
+/// PEM public key of the releases, RSA or EC, pinned when building. Builds
+/// without it only tell where the update is.
+const RELEASE_KEY: Option<&str> = option_env!("CHECK_QUALYS_RELEASE_KEY");
+
+#[derive(Args, Debug)]
+pub struct SelfUpdateArgs {
+    /// GitHub repository publishing the releases
+    #[arg(long, default_value = "desylva/nagios")]
+    pub repository: String,
+
+    /// Only tell whether a newer release is available
+    #[arg(long)]
+    pub check: bool,
+
+    /// Install the latest release even if it isn't newer
+    #[arg(long)]
+    pub force: bool,
+}
+
+#[derive(Deserialize, Debug)]
+struct Release {
+    tag_name: String,
+    assets: Vec<Asset>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Asset {
+    name: String,
+    browser_download_url: String,
+}
+
+impl Release {
+    fn asset(&self, name: &str) -> Result<&Asset, Box<dyn Error>> {
+        self.assets
+            .iter()
+            .find(|asset| asset.name == name)
+            .ok_or_else(|| tr("release_asset_missing", &[&self.tag_name, &name]).into())
+    }
+
+    /// Version of the release, tags look like `v1.2.3` or `check_qualys-v1.2.3`.
+    fn version(&self) -> &str {
+        let tag = self.tag_name.rsplit('-').next().unwrap_or_default();
+        tag.trim_start_matches('v')
+    }
+}
+
+/// Replace the running binary with the latest release once the checksums
+/// bear the signature of the pinned release key and the binary matches its
+/// own. The checksums alone come from the same release, anyone able to
+/// tamper with it could change both.
+pub fn self_update(args: &SelfUpdateArgs, client: Client) -> Result<(), Box<dyn Error>> {
+    let url = format!(
+        "https://api.github.com/repos/{}/releases/latest",
+        args.repository
+    );
+    let release: Release = client.get(url).send()?.error_for_status()?.json()?;
+
+    let current = env!("CARGO_PKG_VERSION");
+    let newer = is_newer(release.version(), current);
+    if args.check || !(newer || args.force) {
+        match newer {
+            true => println!(
+                "{}",
+                tr("update_available", &[&release.version(), &current])
+            ),
+            false => println!("{}", tr("up_to_date", &[&current])),
+        }
+        return Ok(());
+    }
+
+    let name = format!("check_qualys-{}-{}", env::consts::ARCH, env::consts::OS);
+    let Some(key) = RELEASE_KEY else {
+        let url = &release.asset(&name)?.browser_download_url;
+        println!("{}", tr("update_unverifiable", &[&release.version(), url]));
+        return Ok(());
+    };
+    let download = |asset: &str| -> Result<Vec<u8>, Box<dyn Error>> {
+        let url = &release.asset(asset)?.browser_download_url;
+        Ok(client
+            .get(url)
+            .send()?
+            .error_for_status()?
+            .bytes()?
+            .to_vec())
+    };
+    let checksums = download(CHECKSUMS_ASSET)?;
+    let signature = download(SIGNATURE_ASSET)?;
+    let key = PKey::public_key_from_pem(key.as_bytes())?;
+    let mut verifier = Verifier::new(MessageDigest::sha256(), &key)?;
+    verifier.update(&checksums)?;
+    if !verifier.verify(&signature)? {
+        return Err(tr(
+            "bad_release_signature",
+            &[&CHECKSUMS_ASSET, &release.tag_name],
+        )
+        .into());
+    }
+    let checksums = String::from_utf8(checksums)?;
+    let expected = checksums
+        .lines()
+        .filter_map(|line| line.split_once(char::is_whitespace))
+        .find(|(_, file)| file.trim().trim_start_matches('*') == name)
+        .map(|(sum, _)| sum.to_lowercase())
+        .ok_or_else(|| tr("checksum_missing", &[&name, &CHECKSUMS_ASSET]))?;
+    let binary = download(&name)?;
+    let actual: String = Sha256::digest(&binary)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    if actual != expected {
+        return Err(tr("checksum_mismatch", &[&name, &actual, &expected]).into());
+    }
+
+    // Renaming within the same directory swaps the binary atomically
+    let exe = env::current_exe()?;
+    let staged = exe.with_extension("new");
+    fs::write(&staged, &binary)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&staged, fs::Permissions::from_mode(0o755))?;
+    }
+    fs::rename(&staged, &exe)?;
+    println!("{}", tr("updated", &[&current, &release.version()]));
+    Ok(())
+}
+
+/// Compare dotted version numbers, non numeric parts count as zero and so
+/// do missing ones, 1.2 being 1.2.0.
+fn is_newer(candidate: &str, current: &str) -> bool {
+    let parse = |version: &str| -> Vec<u64> {
+        version
+            .split('.')
+            .map(|part| part.parse().unwrap_or_default())
+            .collect()
+    };
+    let (mut candidate, mut current) = (parse(candidate), parse(current));
+    let len = candidate.len().max(current.len());
+    candidate.resize(len, 0);
+    current.resize(len, 0);
+    candidate > current
+}
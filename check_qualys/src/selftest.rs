@@ -0,0 +1,152 @@
+use crate::i18n::tr;
+use crate::inventory::Inventory;
+use crate::output::ServiceState;
+use crate::state::StateFile;
+use crate::tape::Tape;
+use crate::{api_client, get_info, runtime, scheduler, Cli, HTTP_TIMEOUT, NAGIOS_TIMEOUT};
+use std::fs::{self, OpenOptions};
+
+/// Outcome of one of the checks, about the subject named by a catalog key.
+struct Diagnosis {
+    state: ServiceState,
+    subject: &'static str,
+    detail: String,
+}
+
+impl Diagnosis {
+    fn new(state: ServiceState, subject: &'static str, detail: String) -> Diagnosis {
+        Diagnosis {
+            state,
+            subject,
+            detail,
+        }
+    }
+}
+
+/// Check what a run with the same options relies on, print a diagnosis of
+/// each item and return the exit code of the worst one.
+pub fn selftest(cli: &Cli) -> i32 {
+    let diagnoses = [
+        check_inventory(cli),
+        check_budget(cli),
+        check_api(cli),
+        check_state_file(cli),
+        check_audit_log(cli),
+    ];
+    let mut exit_code = 0;
+    for diagnosis in diagnoses.iter().flatten() {
+        println!(
+            "{}: {}: {}",
+            diagnosis.state,
+            tr(diagnosis.subject, &[]),
+            diagnosis.detail
+        );
+        exit_code = exit_code.max(diagnosis.state as i32);
+    }
+    exit_code
+}
+
+fn check_inventory(cli: &Cli) -> Option<Diagnosis> {
+    let path = cli.inventory.as_ref()?;
+    let diagnosis = match Inventory::load(path) {
+        Ok(inventory) => {
+            let invalid: Vec<&str> = inventory
+                .hosts
+                .iter()
+                .map(|host| host.domain.as_str())
+                .filter(|domain| addr::parse_domain_name(domain).is_err())
+                .collect();
+            if invalid.is_empty() {
+                let detail = tr("selftest_hosts", &[&inventory.hosts.len(), path]);
+                Diagnosis::new(ServiceState::Ok, "subject_inventory", detail)
+            } else {
+                let detail = tr("selftest_invalid_domains", &[path, &invalid.join(", ")]);
+                Diagnosis::new(ServiceState::Critical, "subject_inventory", detail)
+            }
+        }
+        Err(e) => Diagnosis::new(
+            ServiceState::Critical,
+            "subject_inventory",
+            format!("{}: {}", path, e),
+        ),
+    };
+    Some(diagnosis)
+}
+
+fn check_budget(cli: &Cli) -> Option<Diagnosis> {
+    let budget = scheduler::budget(cli).as_secs();
+    let (state, limit) = match cli.timeout {
+        Some(timeout) if budget > timeout => (ServiceState::Warning, timeout),
+        Some(timeout) => (ServiceState::Ok, timeout),
+        None if budget > NAGIOS_TIMEOUT => (ServiceState::Warning, NAGIOS_TIMEOUT),
+        None => (ServiceState::Ok, NAGIOS_TIMEOUT),
+    };
+    let detail = tr("selftest_budget", &[&budget, &limit]);
+    Some(Diagnosis::new(state, "subject_timeout", detail))
+}
+
+fn check_api(cli: &Cli) -> Option<Diagnosis> {
+    let mut tape = Tape::new(cli);
+    let info = api_client(cli)
+        .and_then(|client| runtime()?.block_on(get_info(&client, cli, &mut tape, HTTP_TIMEOUT)));
+    let diagnosis = match info {
+        Ok(info) => {
+            let unknown = tr("selftest_unknown", &[]);
+            let detail = tr(
+                "selftest_api",
+                &[
+                    &info.engine_version.as_deref().unwrap_or(&unknown),
+                    &info.criteria_version.as_deref().unwrap_or(&unknown),
+                    &info.current_assessments.unwrap_or_default(),
+                    &info.max_assessments.unwrap_or_default(),
+                ],
+            );
+            Diagnosis::new(ServiceState::Ok, "subject_api", detail)
+        }
+        Err(e) => Diagnosis::new(ServiceState::Critical, "subject_api", e.to_string()),
+    };
+    Some(diagnosis)
+}
+
+/// The state file is written through a temporary file next to it, which
+/// is what gets probed.
+fn check_state_file(cli: &Cli) -> Option<Diagnosis> {
+    let path = cli.state_file.as_ref()?;
+    let diagnosis = match StateFile::load(path) {
+        Ok(state) => {
+            let probe = format!("{}.tmp", path);
+            match fs::write(&probe, "").and_then(|_| fs::remove_file(&probe)) {
+                Ok(()) => {
+                    let detail = tr("selftest_state_writable", &[path, &state.domains.len()]);
+                    Diagnosis::new(ServiceState::Ok, "subject_state_file", detail)
+                }
+                Err(e) => {
+                    let detail = tr("selftest_not_writable", &[path, &e]);
+                    Diagnosis::new(ServiceState::Critical, "subject_state_file", detail)
+                }
+            }
+        }
+        Err(e) => Diagnosis::new(
+            ServiceState::Critical,
+            "subject_state_file",
+            format!("{}: {}", path, e),
+        ),
+    };
+    Some(diagnosis)
+}
+
+fn check_audit_log(cli: &Cli) -> Option<Diagnosis> {
+    let path = cli.audit_log.as_ref()?;
+    let diagnosis = match OpenOptions::new().create(true).append(true).open(path) {
+        Ok(_) => Diagnosis::new(
+            ServiceState::Ok,
+            "subject_audit_log",
+            tr("selftest_writable", &[path]),
+        ),
+        Err(e) => {
+            let detail = tr("selftest_not_writable", &[path, &e]);
+            Diagnosis::new(ServiceState::Critical, "subject_audit_log", detail)
+        }
+    };
+    Some(diagnosis)
+}
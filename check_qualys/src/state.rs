@@ -0,0 +1,85 @@
+use crate::{State, Status};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::io::ErrorKind;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Results kept on disk between runs.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Default)]
+pub struct StateFile {
+    /// Domains already assessed by the batch run in progress
+    #[serde(default)]
+    pub checkpoint: Vec<String>,
+    /// Last result of every domain
+    #[serde(default)]
+    pub domains: BTreeMap<String, Record>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+pub struct Record {
+    /// Seconds since the epoch when the assessment completed
+    pub completed: u64,
+    #[serde(flatten)]
+    pub status: Status,
+}
+
+impl StateFile {
+    /// Load the state file, a missing one is an empty state.
+    pub fn load(path: &str) -> Result<StateFile, Box<dyn Error>> {
+        match fs::read_to_string(path) {
+            Ok(content) => {
+                Ok(serde_json::from_str(&content).map_err(|e| format!("{}: {}", path, e))?)
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(StateFile::default()),
+            Err(e) => Err(format!("{}: {}", path, e).into()),
+        }
+    }
+
+    /// Write the state through a temporary file so an interrupted run
+    /// never leaves a truncated state behind.
+    pub fn save(&self, path: &str) -> Result<(), Box<dyn Error>> {
+        let tmp = format!("{}.tmp", path);
+        fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// The result of a domain completed earlier in the current batch run.
+    pub fn resumed(&self, domain: &str) -> Option<&Status> {
+        if !self.checkpoint.iter().any(|done| done == domain) {
+            return None;
+        }
+        self.domains.get(domain).map(|record| &record.status)
+    }
+
+    /// The result of a domain whose assessment completed less than
+    /// `minutes` ago, in an earlier run or the current one.
+    pub fn fresh(&self, domain: &str, minutes: u64) -> Option<&Status> {
+        let record = self.domains.get(domain)?;
+        let age = now().saturating_sub(record.completed);
+        (record.status.status == State::Ready && age < minutes * 60).then_some(&record.status)
+    }
+
+    pub fn complete(&mut self, domain: &str, status: &Status) {
+        let completed = now();
+        self.domains.insert(
+            domain.to_string(),
+            Record {
+                completed,
+                status: status.clone(),
+            },
+        );
+        self.checkpoint.push(domain.to_string());
+    }
+}
+
+/// Seconds since the epoch.
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
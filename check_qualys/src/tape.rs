@@ -0,0 +1,135 @@
+//! API responses saved by `--record` and played back by `--replay`.
+//!
+//! Every response is a JSON file named after the domain it is about and
+//! its rank among the responses about it, e.g. `www.example.com-003.json`,
+//! so a replayed run polls through the same responses as the recorded one.
+//! Once they are exhausted the last one is replayed over and over.
+//!
+//! `--from-file` plays a single saved `analyze` response, such as one
+//! attached to a bug report, for whatever domain is polled.
+
+use crate::Cli;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Name the responses of the `info` call are saved under.
+pub const INFO: &str = "_info";
+
+/// An API response along what the plugin reads of its headers.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Exchange {
+    /// HTTP status code
+    pub status: u16,
+    pub max_assessments: Option<usize>,
+    pub current_assessments: Option<usize>,
+    /// Seconds the `Retry-After` header asks to wait for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_after: Option<u64>,
+    /// Decoded JSON body, or the raw body as a string when it isn't JSON
+    pub body: Value,
+}
+
+#[derive(Debug, PartialEq)]
+enum Mode {
+    Off,
+    Record,
+    Replay,
+    /// Playing the response of `--from-file`
+    File,
+}
+
+pub struct Tape {
+    mode: Mode,
+    dir: PathBuf,
+    /// Number of responses already recorded or replayed, by name
+    positions: HashMap<String, usize>,
+}
+
+impl Tape {
+    pub fn new(cli: &Cli) -> Tape {
+        let (mode, dir) = match (&cli.record, &cli.replay, &cli.from_file) {
+            (Some(dir), _, _) => (Mode::Record, dir.as_str()),
+            (None, Some(dir), _) => (Mode::Replay, dir.as_str()),
+            (None, None, Some(path)) => (Mode::File, path.as_str()),
+            (None, None, None) => (Mode::Off, ""),
+        };
+        Tape {
+            mode,
+            dir: PathBuf::from(dir),
+            positions: HashMap::new(),
+        }
+    }
+
+    pub fn recording(&self) -> bool {
+        self.mode == Mode::Record
+    }
+
+    /// Whether responses are played rather than received, from a
+    /// directory or `--from-file`.
+    pub fn replaying(&self) -> bool {
+        matches!(self.mode, Mode::Replay | Mode::File)
+    }
+
+    fn path(&self, name: &str, position: usize) -> PathBuf {
+        self.dir.join(format!("{}-{:03}.json", name, position))
+    }
+
+    /// The next response about `name` when replaying, none otherwise.
+    pub fn replay(&mut self, name: &str) -> Result<Option<Exchange>, Box<dyn Error>> {
+        match self.mode {
+            Mode::Off | Mode::Record => return Ok(None),
+            Mode::File if name == INFO => {
+                return Err(format!("no API info in {}", self.dir.display()).into())
+            }
+            Mode::File => return Ok(Some(load_response(&self.dir)?)),
+            Mode::Replay => (),
+        }
+        let position = self.positions.get(name).copied().unwrap_or_default();
+        let mut path = self.path(name, position);
+        if !path.exists() && position > 0 {
+            path = self.path(name, position - 1);
+        } else {
+            self.positions.insert(name.to_string(), position + 1);
+        }
+        Ok(Some(load(&path)?))
+    }
+
+    /// Save a response about `name` when recording.
+    pub fn record(&mut self, name: &str, exchange: &Exchange) -> Result<(), Box<dyn Error>> {
+        if !self.recording() {
+            return Ok(());
+        }
+        let position = self.positions.get(name).copied().unwrap_or_default();
+        fs::create_dir_all(&self.dir)?;
+        fs::write(
+            self.path(name, position),
+            serde_json::to_string_pretty(exchange)?,
+        )?;
+        self.positions.insert(name.to_string(), position + 1);
+        Ok(())
+    }
+}
+
+/// A saved `analyze` response, as received from the API.
+pub fn load_response(path: &Path) -> Result<Exchange, Box<dyn Error>> {
+    let content = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let body = serde_json::from_str(&content).map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(Exchange {
+        status: 200,
+        max_assessments: None,
+        current_assessments: None,
+        retry_after: None,
+        body,
+    })
+}
+
+fn load(path: &Path) -> Result<Exchange, Box<dyn Error>> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("no recorded response {}: {}", path.display(), e))?;
+    Ok(serde_json::from_str(&content)?)
+}
@@ -0,0 +1,105 @@
+//! Status line templates of `--format`, e.g. `{{domain}}: {{grade}} ({{score}})`.
+//!
+//! Variables go between double braces and are checked when the options are
+//! parsed. Those without a value, such as the grade of a domain that
+//! couldn't be assessed, are left empty. The perfdata still follows the line.
+
+use std::str::FromStr;
+
+/// Variables a template can use.
+pub const VARIABLES: [&str; 8] = [
+    "state", "domain", "grade", "score", "reason", "details", "tags", "summary",
+];
+
+#[derive(Clone, Debug)]
+enum Part {
+    Text(String),
+    Variable(&'static str),
+}
+
+#[derive(Clone, Debug)]
+pub struct Template(Vec<Part>);
+
+impl FromStr for Template {
+    type Err = String;
+
+    fn from_str(template: &str) -> Result<Self, Self::Err> {
+        let mut parts = Vec::new();
+        let mut rest = template;
+        while let Some(start) = rest.find("{{") {
+            if start > 0 {
+                parts.push(Part::Text(rest[..start].to_string()));
+            }
+            let end = rest[start..]
+                .find("}}")
+                .ok_or_else(|| format!("{}: unclosed {{{{", &rest[start..]))?;
+            let name = rest[start + 2..start + end].trim();
+            let variable = VARIABLES
+                .iter()
+                .find(|variable| **variable == name)
+                .ok_or_else(|| {
+                    format!(
+                        "unknown variable {} (expected {})",
+                        name,
+                        VARIABLES.join(", ")
+                    )
+                })?;
+            parts.push(Part::Variable(variable));
+            rest = &rest[start + end + 2..];
+        }
+        if !rest.is_empty() {
+            parts.push(Part::Text(rest.to_string()));
+        }
+        Ok(Template(parts))
+    }
+}
+
+impl Template {
+    /// The line with every variable replaced by its value.
+    pub fn render(&self, value: impl Fn(&str) -> Option<String>) -> String {
+        self.0
+            .iter()
+            .map(|part| match part {
+                Part::Text(text) => text.clone(),
+                Part::Variable(name) => value(name).unwrap_or_default(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_render() {
+        let cases: &[(&str, &str)] = &[
+            (
+                "{{domain}}: {{grade}} ({{score}})",
+                "www.example.com: A (90)",
+            ),
+            ("{{ state }} {{reason}}", "OK "),
+            ("plain text", "plain text"),
+            ("{{domain}}{{grade}}", "www.example.comA"),
+            ("{single} {{grade}}", "{single} A"),
+        ];
+        let value = |name: &str| match name {
+            "domain" => Some("www.example.com".to_string()),
+            "grade" => Some("A".to_string()),
+            "score" => Some("90".to_string()),
+            "state" => Some("OK".to_string()),
+            _ => None,
+        };
+        for (template, expected) in cases {
+            let template: Template = template.parse().unwrap();
+            assert_eq!(template.render(value), *expected);
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        for template in ["{{domain", "{{nope}}", "{{}}", "ok {{grade}} {{"] {
+            assert!(template.parse::<Template>().is_err(), "{}", template);
+        }
+    }
+}